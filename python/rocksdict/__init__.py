@@ -1,3 +1,5 @@
+from collections.abc import MutableMapping
+
 from .rocksdict import *
 
 __doc__ = rocksdict.__doc__
@@ -5,6 +7,7 @@ __doc__ = rocksdict.__doc__
 __all__ = ["Rdict",
            "WriteBatch",
            "SstFileWriter",
+           "BulkLoader",
            "AccessType",
            "WriteOptions",
            "Snapshot",
@@ -24,15 +27,33 @@ __all__ = ["Rdict",
            "DataBlockIndexType",
            "BlockBasedIndexType",
            "Cache",
+           "WriteBufferManager",
            "ChecksumType",
+           "PinningTier",
            "DBCompactionStyle",
+           "DBCompactionPri",
            "DBCompressionType",
            "DBRecoveryMode",
            "Env",
            "FifoCompactOptions",
            "CompactOptions",
            "BottommostLevelCompaction",
-           "KeyEncodingType"]
+           "KeyEncodingType",
+           "Codec",
+           "ValueCompression",
+           "DbClosedError",
+           "RocksDBError",
+           "CorruptionError",
+           "RocksIOError",
+           "LockError",
+           "BusyError",
+           "TimedOutError",
+           "TryAgainError",
+           "NotSupportedError",
+           "InvalidArgumentError",
+           "IncompleteError"]
 
 Rdict.__enter__ = lambda self: self
 Rdict.__exit__ = lambda self, exc_type, exc_val, exc_tb: self.close()
+
+MutableMapping.register(Rdict)
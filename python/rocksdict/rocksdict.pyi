@@ -1,4 +1,5 @@
-from typing import Any, Union, List, Iterator, Tuple, Dict, overload, Callable
+import os
+from typing import Any, Union, List, Iterator, Iterable, Tuple, Dict, Mapping, overload, Callable
 
 __all__ = ["Rdict",
            "RdictIter",
@@ -16,13 +17,17 @@ __all__ = ["Rdict",
            "DataBlockIndexType",
            "BlockBasedIndexType",
            "Cache",
+           "WriteBufferManager",
            "ChecksumType",
+           "PinningTier",
            "DBCompactionStyle",
+           "DBCompactionPri",
            "DBCompressionType",
            "DBRecoveryMode",
            "Env",
            "FifoCompactOptions",
            "SstFileWriter",
+           "BulkLoader",
            "IngestExternalFileOptions",
            "WriteBatch",
            "ColumnFamily",
@@ -31,7 +36,19 @@ __all__ = ["Rdict",
            "CompactOptions",
            "BottommostLevelCompaction",
            "KeyEncodingType",
-           "DbClosedError"]
+           "Codec",
+           "ValueCompression",
+           "DbClosedError",
+           "RocksDBError",
+           "CorruptionError",
+           "RocksIOError",
+           "LockError",
+           "BusyError",
+           "TimedOutError",
+           "TryAgainError",
+           "NotSupportedError",
+           "InvalidArgumentError",
+           "IncompleteError"]
 
 class DataBlockIndexType:
     @staticmethod
@@ -59,6 +76,16 @@ class ChecksumType:
     @staticmethod
     def xxh3() -> ChecksumType: ...
 
+class PinningTier:
+    @staticmethod
+    def fallback() -> PinningTier: ...
+    @staticmethod
+    def none() -> PinningTier: ...
+    @staticmethod
+    def flushed_and_similar() -> PinningTier: ...
+    @staticmethod
+    def all() -> PinningTier: ...
+
 class BlockBasedOptions:
     def __init__(self) -> None: ...
     def disable_cache(self) -> None: ...
@@ -77,6 +104,14 @@ class BlockBasedOptions:
     def set_pin_l0_filter_and_index_blocks_in_cache(self, v: bool) -> None: ...
     def set_pin_top_level_index_and_filter(self, v: bool) -> None: ...
     def set_checksum_type(self, checksum_type: ChecksumType) -> None: ...
+    def set_ribbon_filter(self, bloom_equivalent_bits_per_key: float) -> None: ...
+    def set_hybrid_ribbon_filter(self, bloom_equivalent_bits_per_key: float, bloom_before_level: int) -> None: ...
+    def set_whole_key_filtering(self, v: bool) -> None: ...
+    def set_optimize_filters_for_memory(self, v: bool) -> None: ...
+    def set_cache_index_and_filter_blocks_with_high_priority(self, v: bool) -> None: ...
+    def set_top_level_index_pinning_tier(self, tier: PinningTier) -> None: ...
+    def set_partition_pinning_tier(self, tier: PinningTier) -> None: ...
+    def set_unpartitioned_pinning_tier(self, tier: PinningTier) -> None: ...
 
 class Cache:
     def __init__(self, capacity: int) -> None: ...
@@ -85,6 +120,15 @@ class Cache:
     def get_pinned_usage(self) -> int: ...
     def get_usage(self) -> int: ...
     def set_capacity(self, capacity: int) -> None: ...
+    def get_capacity(self) -> int: ...
+
+class WriteBufferManager:
+    def __init__(self, buffer_size: int, allow_stall: bool = False, cache: Union[Cache, None] = None) -> None: ...
+    def usage(self) -> int: ...
+    def buffer_size(self) -> int: ...
+    def set_buffer_size(self, buffer_size: int) -> None: ...
+    def enabled(self) -> bool: ...
+    def set_allow_stall(self, allow_stall: bool) -> None: ...
 
 class CuckooTableOptions:
     def __init__(self) -> None: ...
@@ -94,6 +138,18 @@ class CuckooTableOptions:
     def set_max_search_depth(self, depth: int) -> None: ...
     def set_use_module_hash(self, flag: bool) -> None: ...
 
+class DBCompactionPri:
+    @staticmethod
+    def by_compensated_size() -> DBCompactionPri: ...
+    @staticmethod
+    def min_overlapping_ratio() -> DBCompactionPri: ...
+    @staticmethod
+    def oldest_largest_seq_first() -> DBCompactionPri: ...
+    @staticmethod
+    def oldest_smallest_seq_first() -> DBCompactionPri: ...
+    @staticmethod
+    def round_robin() -> DBCompactionPri: ...
+
 class DBCompactionStyle:
     @staticmethod
     def fifo() -> DBCompactionStyle: ...
@@ -171,6 +227,12 @@ class Options:
     def __init__(self, raw_mode: bool = False) -> None: ...
     @staticmethod
     def load_latest(path: str, env: Env = Env(), ignore_unknown_options: bool = False, cache: Cache = Cache(8 * 1024 * 1024)) -> Tuple[Options, Dict[str, Options]]: ...
+    @staticmethod
+    def from_dict(options: Dict[str, Union[bool, int, float, str]], raw_mode: bool = False) -> Options: ...
+    @staticmethod
+    def from_options_string(options: str, raw_mode: bool = False) -> Options: ...
+    def add_compact_on_deletion_collector_factory(self, window_size: int, num_dels_trigger: int, deletion_ratio: float = 0.0) -> None: ...
+    def check_compatibility(self, path: str, column_families: Union[Dict[str, Options], None] = None) -> Dict[str, Dict[str, Dict[str, Tuple[Any, Any]]]]: ...
     def create_if_missing(self, create_if_missing: bool) -> None: ...
     def create_missing_column_families(self, create_missing_cfs: bool) -> None: ...
     def enable_statistics(self) -> None: ...
@@ -186,9 +248,19 @@ class Options:
     def set_allow_mmap_writes(self, is_enabled: bool) -> None: ...
     def set_arena_block_size(self, size: int) -> None: ...
     def set_atomic_flush(self, atomic_flush: bool) -> None: ...
+    def set_blob_compaction_readahead_size(self, val: int) -> None: ...
+    def set_blob_compression_type(self, t: DBCompressionType) -> None: ...
+    def set_blob_file_size(self, val: int) -> None: ...
+    def set_blob_file_starting_level(self, level: int) -> None: ...
+    def set_blob_gc_age_cutoff(self, val: float) -> None: ...
+    def set_blob_gc_force_threshold(self, val: float) -> None: ...
     def set_block_based_table_factory(self, factory: BlockBasedOptions) -> None: ...
     def set_bloom_locality(self, v: int) -> None: ...
+    def set_bottommost_compression_options(self, w_bits: int, level: int, strategy: int, max_dict_bytes: int, enabled: bool) -> None: ...
+    def set_bottommost_compression_type(self, t: DBCompressionType) -> None: ...
+    def set_bottommost_zstd_max_train_bytes(self, value: int, enabled: bool) -> None: ...
     def set_bytes_per_sync(self, nbytes: int) -> None: ...
+    def set_compaction_pri(self, pri: DBCompactionPri) -> None: ...
     def set_compaction_readahead_size(self, compaction_readahead_size: int) -> None: ...
     def set_compaction_style(self, style: DBCompactionStyle) -> None: ...
     def set_compression_options(self, w_bits: int, level: int, strategy: int, max_dict_bytes: int) -> None: ...
@@ -201,6 +273,9 @@ class Options:
     def set_delete_obsolete_files_period_micros(self, micros: int) -> None: ...
     def set_disable_auto_compactions(self, disable: bool) -> None: ...
     def set_dump_malloc_stats(self, enabled: bool) -> None: ...
+    def set_enable_blob_files(self, val: bool) -> None: ...
+    def set_enable_blob_gc(self, val: bool) -> None: ...
+    def set_enable_namespaces(self, enabled: bool) -> None: ...
     def set_enable_pipelined_write(self, value: bool) -> None: ...
     def set_enable_write_thread_adaptive_yield(self, enabled: bool) -> None: ...
     def set_env(self, env: Env) -> None: ...
@@ -236,14 +311,18 @@ class Options:
     def set_memtable_huge_page_size(self, size: int) -> None: ...
     def set_memtable_prefix_bloom_ratio(self, ratio: float) -> None: ...
     def set_memtable_whole_key_filtering(self, whole_key_filter: bool) -> None: ...
+    def set_min_blob_size(self, val: int) -> None: ...
     def set_min_level_to_compress(self, lvl: int) -> None: ...
     def set_min_write_buffer_number(self, nbuf: int) -> None: ...
     def set_min_write_buffer_number_to_merge(self, to_merge: int) -> None: ...
     def set_num_levels(self, n: int) -> None: ...
     def set_optimize_filters_for_hits(self, optimize_for_hits: bool) -> None: ...
     def set_paranoid_checks(self, enabled: bool) -> None: ...
+    def set_periodic_compaction_seconds(self, secs: int) -> None: ...
     def set_plain_table_factory(self, options: PlainTableFactoryOptions) -> None: ...
     def set_prefix_extractor(self, prefix_extractor: SliceTransform) -> None: ...
+    def set_codec(self, codec: Codec) -> None: ...
+    def set_value_compression(self, compression: Union[ValueCompression, None]) -> None: ...
     def set_ratelimiter(self, rate_bytes_per_sec: int, refill_period_us: int, fairness: int) -> None: ...
     def set_recycle_log_file_num(self, num: int) -> None: ...
     def set_report_bg_io_stats(self, enable: bool) -> None: ...
@@ -256,6 +335,7 @@ class Options:
     def set_table_cache_num_shard_bits(self, nbits: int) -> None: ...
     def set_target_file_size_base(self, size: int) -> None: ...
     def set_target_file_size_multiplier(self, multiplier: int) -> None: ...
+    def set_ttl(self, secs: int) -> None: ...
     def set_universal_compaction_options(self, uco: UniversalCompactOptions) -> None: ...
     def set_unordered_write(self, unordered: bool) -> None: ...
     def set_use_adaptive_mutex(self, enabled: bool) -> None: ...
@@ -268,8 +348,11 @@ class Options:
     def set_wal_size_limit_mb(self, size: int) -> None: ...
     def set_wal_ttl_seconds(self, secs: int) -> None: ...
     def set_writable_file_max_buffer_size(self, nbytes: int) -> None: ...
+    def set_write_buffer_manager(self, write_buffer_manager: WriteBufferManager) -> None: ...
     def set_write_buffer_size(self, size: int) -> None: ...
     def set_zstd_max_train_bytes(self, value: int) -> None: ...
+    def to_dict(self) -> Dict[str, Union[bool, int, float, str]]: ...
+    def to_options_string(self) -> str: ...
 
 class PlainTableFactoryOptions:
     @property
@@ -374,17 +457,36 @@ class WriteOptions:
 class Rdict:
     def __init__(self, path: str,
                  options: Union[Options, None] = None,
-                 column_families: Union[Dict[str, Options], None] = None,
-                 access_type: AccessType = AccessType.read_write()) -> None: ...
+                 column_families: Union[Dict[str, Union[Options, Tuple[Options, Union[int, None]]]], None] = None,
+                 access_type: AccessType = AccessType.read_write(),
+                 open_all_column_families: bool = False,
+                 verify_options: bool = False) -> None: ...
     def __enter__(self) -> Rdict: ...
     def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
     def set_loads(self, dumps: Callable[[bytes], Any]) -> None: ...
+    def set_codec(self, codec: Codec) -> None: ...
+    def get_codec(self) -> Codec: ...
+    def set_value_compression(self, compression: Union[ValueCompression, None] = None) -> None: ...
     def set_read_options(self, read_opt: ReadOptions) -> None: ...
     def set_write_options(self, write_opt: WriteOptions) -> None: ...
     def __contains__(self, key: Union[str, int, float, bytes, bool]) -> bool: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    @overload
+    def __getitem__(self, key: slice) -> RdictRange: ...
+    @overload
     def __getitem__(self, key: Union[str, int, float, bytes, bool, List[Union[str, int, float, bytes, bool]]]) -> Any | None: ...
     def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> RdictKeys: ...
+    def __eq__(self, other: object) -> bool: ...
+    def update(self, other: Union[Mapping[Any, Any], Iterable[Tuple[Any, Any]], None] = None, **kwargs: Any) -> None: ...
+    @overload
+    def pop(self, key: Union[str, int, float, bytes, bool]) -> Any: ...
+    @overload
+    def pop(self, key: Union[str, int, float, bytes, bool], default: Any) -> Any: ...
+    def popitem(self) -> Tuple[Union[str, int, float, bytes, bool], Any]: ...
+    def setdefault(self, key: Union[str, int, float, bytes, bool], default: Any = None) -> Any: ...
+    def clear(self) -> None: ...
     def get(self,
             key: Union[str, int, float, bytes, bool, List[Union[str, int, float, bytes, bool]]],
             default: Any = None,
@@ -411,6 +513,8 @@ class Rdict:
     def ingest_external_file(self, paths: List[str], opts: IngestExternalFileOptions = IngestExternalFileOptions()) -> None: ...
     def get_column_family(self, name: str) -> Rdict: ...
     def get_column_family_handle(self, name: str) -> ColumnFamily: ...
+    def namespace(self, prefix: Union[str, bytes]) -> Rdict: ...
+    def column_families(self) -> List[str]: ...
     def drop_column_family(self, name: str) -> None: ...
     def create_column_family(self, name: str, options: Options = Options()) -> Rdict: ...
     def write(self, write_batch: WriteBatch, write_opt: Union[WriteOptions, None] = None) -> None: ...
@@ -421,10 +525,23 @@ class Rdict:
     def snapshot(self) -> Snapshot: ...
     def path(self) -> str: ...
     def set_options(self, options: Dict[str, str]) -> None: ...
+    def get_options(self) -> Dict[str, Union[bool, int, float, str]]: ...
     def property_value(self, name: str) -> Union[str, None]: ...
     def property_int_value(self, name: str) -> Union[int, None]: ...
+    def estimate_num_keys(self) -> int: ...
+    def count(self,
+              start: Union[str, int, float, bytes, bool, None] = None,
+              end: Union[str, int, float, bytes, bool, None] = None,
+              prefix: Union[str, bytes, None] = None) -> int: ...
+    def approximate_sizes(self, ranges: List[Tuple[Any, Any]]) -> List[int]: ...
     def latest_sequence_number(self) -> int: ...
     def live_files(self) -> List[Dict[str, Any]]: ...
+    def column_family_metadata(self) -> Dict[str, Any]: ...
+    def get_properties_of_all_tables(self) -> Dict[str, Dict[str, Any]]: ...
+    def get_properties_of_tables_in_range(self, ranges: List[Tuple[Any, Any]]) -> Dict[str, Dict[str, Any]]: ...
+    def aggregated_table_properties(self, level: Union[int, None] = None) -> Dict[str, Any]: ...
+    def blob_stats(self) -> Dict[str, Union[int, None]]: ...
+    def block_cache_entry_stats(self) -> Dict[str, Any]: ...
     def compact_range(self, begin: Union[str, int, float, bytes, bool, None],
                       end: Union[str, int, float, bytes, bool, None],
                       compact_opt: CompactOptions = CompactOptions()) -> None: ...
@@ -453,6 +570,15 @@ class RdictValues(Iterator[Any]):
     def __iter__(self) -> RdictValues: ...
     def __next__(self) -> Any: ...
 
+class RdictRange:
+    def __iter__(self) -> RdictKeys: ...
+    def __reversed__(self) -> RdictKeys: ...
+    def keys(self, backwards: bool = False) -> RdictKeys: ...
+    def values(self, backwards: bool = False) -> RdictValues: ...
+    def items(self, backwards: bool = False) -> RdictItems: ...
+    def count(self) -> int: ...
+    def delete(self) -> None: ...
+
 class RdictIter:
     def valid(self) -> bool: ...
     def status(self) -> None: ...
@@ -476,11 +602,29 @@ class IngestExternalFileOptions:
 class SstFileWriter:
     def __init__(self, options: Options = Options()) -> None: ...
     def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
+    def set_codec(self, codec: Codec) -> None: ...
     def open(self, path: str) -> None: ...
     def finish(self) -> None: ...
     def file_size(self) -> int: ...
     def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def put_many(self, items: Union[Mapping[Any, Any], Iterable[Tuple[Any, Any]]]) -> None: ...
+    def merge(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def delete_range(self, begin: Union[str, int, float, bytes, bool], end: Union[str, int, float, bytes, bool]) -> None: ...
+    def file_info(self) -> Dict[str, Any]: ...
+
+class BulkLoader:
+    def __init__(self,
+                 options: Options = Options(),
+                 target_file_size: int = 64 * 1024 * 1024,
+                 memory_limit: int = 64 * 1024 * 1024,
+                 temp_dir: Union[str, os.PathLike, None] = None) -> None: ...
+    def set_codec(self, codec: Codec) -> None: ...
+    def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def update(self, items: Union[Mapping[Any, Any], Iterable[Tuple[Any, Any]]]) -> None: ...
+    def __len__(self) -> int: ...
+    def write(self, directory: Union[str, os.PathLike]) -> List[str]: ...
+    def ingest(self, db: Rdict, opts: IngestExternalFileOptions = IngestExternalFileOptions()) -> None: ...
 
 class WriteBatch:
     def __init__(self, raw_mode: bool = False) -> None: ...
@@ -488,6 +632,8 @@ class WriteBatch:
     def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
     def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
+    def set_codec(self, codec: Codec) -> None: ...
+    def set_value_compression(self, compression: Union[ValueCompression, None] = None) -> None: ...
     def set_default_column_family(self, column_family: Union[ColumnFamily, None]) -> None: ...
     def len(self) -> int: ...
     def size_in_bytes(self) -> int: ...
@@ -501,7 +647,11 @@ class WriteBatch:
                      column_family: Union[ColumnFamily, None] = None) -> None: ...
     def clear(self) -> None: ...
 
-class ColumnFamily: ...
+class ColumnFamily:
+    @property
+    def name(self) -> str: ...
+    @property
+    def id(self) -> int: ...
 
 class AccessType:
     @staticmethod
@@ -514,7 +664,11 @@ class AccessType:
     def with_ttl(duration: int) -> AccessType: ...
 
 class Snapshot:
+    @overload
+    def __getitem__(self, key: slice) -> RdictRange: ...
+    @overload
     def __getitem__(self, key: Union[str, int, float, bytes, bool]) -> Any: ...
+    def set_codec(self, codec: Codec) -> None: ...
     def iter(self, read_opt: Union[ReadOptions, None] = None) -> RdictIter: ...
     def items(self, backwards: bool = False,
               from_key: Union[str, int, float, bytes, bool, None] = None,
@@ -549,5 +703,53 @@ class KeyEncodingType:
     @staticmethod
     def prefix() -> KeyEncodingType: ...
 
+class Codec:
+    @staticmethod
+    def pickle() -> Codec: ...
+    @staticmethod
+    def json() -> Codec: ...
+    @staticmethod
+    def msgpack() -> Codec: ...
+    @staticmethod
+    def custom(name: str, dumps: Callable[[Any], bytes], loads: Callable[[bytes], Any]) -> Codec: ...
+    @property
+    def name(self) -> str: ...
+
+class ValueCompression:
+    @staticmethod
+    def zstd(level: int = 3, threshold: int = 1024) -> ValueCompression: ...
+    @staticmethod
+    def lz4(threshold: int = 1024) -> ValueCompression: ...
+
 class DbClosedError(Exception):
     """Raised when accessing a closed database instance."""
+
+class RocksDBError(Exception):
+    """Base class of all errors reported by RocksDB."""
+
+class CorruptionError(RocksDBError):
+    """Raised when RocksDB detects corrupted data."""
+
+class RocksIOError(RocksDBError):
+    """Raised when RocksDB fails to read or write files (named so as not to shadow the builtin `IOError`)."""
+
+class LockError(RocksIOError):
+    """Raised when the database lock is held by another process or instance."""
+
+class BusyError(RocksDBError):
+    """Raised when a resource is busy, the operation may succeed if retried."""
+
+class TimedOutError(RocksDBError):
+    """Raised when an operation timed out, the operation may succeed if retried."""
+
+class TryAgainError(RocksDBError):
+    """Raised when an operation failed transiently, the operation may succeed if retried."""
+
+class NotSupportedError(RocksDBError):
+    """Raised when an operation is not supported."""
+
+class InvalidArgumentError(RocksDBError):
+    """Raised when RocksDB receives an invalid argument or option."""
+
+class IncompleteError(RocksDBError):
+    """Raised when an operation could not complete, e.g. with `no_slowdown` writes."""
@@ -0,0 +1,392 @@
+use crate::codec::CodecPy;
+use crate::encoder::{encode_key, encode_value};
+use crate::namespace::compare_keys;
+use crate::util::to_cpath;
+use crate::{IngestExternalFileOptionsPy, OptionsPy, Rdict, SstFileWriterPy};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rocksdb::Options;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// 64MB default in-memory buffer and SST file sizes
+const DEFAULT_BULK_LOADER_SIZE: usize = 64 * 1024 * 1024;
+
+/// counter to name the temporary directories of the loaders of this process
+static TEMP_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+type Entry = (Box<[u8]>, Box<[u8]>);
+type KeyComparator = fn(&[u8], &[u8]) -> Ordering;
+
+/// Builds SST files from key-value pairs added in any order.
+///
+/// Unlike `SstFileWriter`, which requires keys to be added in increasing
+/// order, `BulkLoader` accepts unsorted input: pairs are buffered in memory,
+/// sorted in Rust with the comparator of the database (int keys are sorted
+/// by value), spilled to temporary files when the buffer is full, and
+/// merged into SST files of about `target_file_size` bytes.
+/// When a key is added several times, the last value wins.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, BulkLoader
+///         import random
+///
+///         keys = list(range(10000))
+///         random.shuffle(keys)
+///
+///         loader = BulkLoader()
+///         for k in keys:
+///             loader[k] = k * k
+///
+///         db = Rdict("tmp")
+///         loader.ingest(db)
+///         assert db[100] == 10000
+///
+///         db.close()
+///         Rdict.destroy("tmp")
+///
+/// Args:
+///     options: this options must have the same `raw_mode` as the Rdict DB.
+///         The codec set by `Options.set_codec` is used to serialize values,
+///         and `Options.set_value_compression` to compress them.
+///     target_file_size: the approximate size of the SST files built.
+///     memory_limit: the approximate size of the in-memory buffer,
+///         beyond which sorted pairs are spilled to temporary files.
+///     temp_dir: the directory of the temporary files,
+///         the system temporary directory by default.
+#[pyclass(name = "BulkLoader")]
+pub(crate) struct BulkLoaderPy {
+    opts: Options,
+    codec: CodecPy,
+    raw_mode: bool,
+    target_file_size: u64,
+    memory_limit: usize,
+    temp_dir: PathBuf,
+    buffer: Vec<Entry>,
+    buffer_size: usize,
+    /// sorted runs spilled to disk, oldest first
+    runs: Vec<PathBuf>,
+    len: usize,
+}
+
+/// reads back the entries of a sorted run
+struct RunReader(BufReader<File>);
+
+impl RunReader {
+    fn read_bytes(&mut self) -> io::Result<Box<[u8]>> {
+        let mut len = [0u8; 8];
+        self.0.read_exact(&mut len)?;
+        let mut bytes = vec![0u8; u64::from_le_bytes(len) as usize];
+        self.0.read_exact(&mut bytes)?;
+        Ok(bytes.into())
+    }
+}
+
+impl Iterator for RunReader {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.0.fill_buf() {
+            Ok(buf) if buf.is_empty() => None,
+            Ok(_) => Some(self.read_bytes().and_then(|k| Ok((k, self.read_bytes()?)))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// the smallest entry of a run, in the merge heap
+struct HeapEntry {
+    entry: Entry,
+    run: usize,
+    cmp: KeyComparator,
+}
+
+impl Ord for HeapEntry {
+    /// smallest key first, then the most recent run first
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.cmp)(&other.entry.0, &self.entry.0).then_with(|| self.run.cmp(&other.run))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for HeapEntry {}
+
+fn bytewise(k1: &[u8], k2: &[u8]) -> Ordering {
+    k1.cmp(k2)
+}
+
+/// sort entries by key, keeping the last value of duplicated keys
+fn sort_entries(mut entries: Vec<Entry>, cmp: KeyComparator) -> Vec<Entry> {
+    entries.sort_by(|e1, e2| cmp(&e1.0, &e2.0));
+    let mut sorted: Vec<Entry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match sorted.last_mut() {
+            Some(last) if cmp(&last.0, &entry.0).is_eq() => *last = entry,
+            _ => sorted.push(entry),
+        }
+    }
+    sorted
+}
+
+impl BulkLoaderPy {
+    fn comparator(&self) -> KeyComparator {
+        if self.raw_mode {
+            bytewise
+        } else {
+            compare_keys
+        }
+    }
+
+    /// sort the in-memory buffer and write it to a new run file
+    fn spill(&mut self) -> PyResult<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.temp_dir)?;
+        let path = self.temp_dir.join(format!("run-{:06}", self.runs.len()));
+        let mut file = BufWriter::new(File::create(&path)?);
+        self.runs.push(path);
+        let entries = sort_entries(std::mem::take(&mut self.buffer), self.comparator());
+        self.buffer_size = 0;
+        for (key, value) in entries {
+            file.write_all(&(key.len() as u64).to_le_bytes())?;
+            file.write_all(&key)?;
+            file.write_all(&(value.len() as u64).to_le_bytes())?;
+            file.write_all(&value)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// merge the runs and the in-memory buffer into SST files in `directory`,
+    /// then reset the loader.
+    fn write_files(&mut self, directory: &Path) -> PyResult<Vec<String>> {
+        let cmp = self.comparator();
+        let mut sources: Vec<Box<dyn Iterator<Item = io::Result<Entry>>>> = Vec::new();
+        for path in &self.runs {
+            sources.push(Box::new(RunReader(BufReader::new(File::open(path)?))));
+        }
+        let buffer = sort_entries(std::mem::take(&mut self.buffer), cmp);
+        sources.push(Box::new(buffer.into_iter().map(Ok)));
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (run, source) in sources.iter_mut().enumerate() {
+            if let Some(entry) = source.next().transpose()? {
+                heap.push(HeapEntry { entry, run, cmp });
+            }
+        }
+        fs::create_dir_all(directory)?;
+        let mut files = Vec::new();
+        let mut writer: Option<SstFileWriterPy> = None;
+        let mut last_key: Option<Box<[u8]>> = None;
+        while let Some(HeapEntry { entry, run, .. }) = heap.pop() {
+            if let Some(next) = sources[run].next().transpose()? {
+                heap.push(HeapEntry {
+                    entry: next,
+                    run,
+                    cmp,
+                });
+            }
+            // an older value of the last key written
+            if matches!(&last_key, Some(last) if cmp(last, &entry.0).is_eq()) {
+                continue;
+            }
+            let mut sst = match writer.take() {
+                Some(sst) => sst,
+                None => {
+                    let path = directory.join(format!("bulk-{:06}.sst", files.len()));
+                    let mut sst =
+                        SstFileWriterPy::new_raw(&self.opts, self.codec.clone(), self.raw_mode);
+                    sst.open_raw(&to_cpath(&path)?)?;
+                    files.push(path.to_string_lossy().into_owned());
+                    sst
+                }
+            };
+            sst.setitem_raw(&entry.0, &entry.1)?;
+            if sst.file_size_raw() >= self.target_file_size {
+                sst.finish_raw()?;
+            } else {
+                writer = Some(sst);
+            }
+            last_key = Some(entry.0);
+        }
+        if let Some(mut sst) = writer {
+            sst.finish_raw()?;
+        }
+        self.clear()?;
+        Ok(files)
+    }
+
+    /// forget the pairs added, deleting the spilled runs
+    fn clear(&mut self) -> PyResult<()> {
+        self.buffer.clear();
+        self.buffer_size = 0;
+        self.len = 0;
+        for run in self.runs.drain(..) {
+            fs::remove_file(run)?;
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl BulkLoaderPy {
+    #[new]
+    #[pyo3(signature = (
+        options = OptionsPy::new(false),
+        target_file_size = DEFAULT_BULK_LOADER_SIZE as u64,
+        memory_limit = DEFAULT_BULK_LOADER_SIZE,
+        temp_dir = None
+    ))]
+    fn new(
+        options: OptionsPy,
+        target_file_size: u64,
+        memory_limit: usize,
+        temp_dir: Option<PathBuf>,
+        py: Python,
+    ) -> PyResult<Self> {
+        let codec = match options.value_codec(py)? {
+            Some(codec) => codec,
+            None => CodecPy::pickle(py)?,
+        };
+        let temp_dir = temp_dir.unwrap_or_else(std::env::temp_dir).join(format!(
+            "rocksdict-bulk-loader-{}-{}",
+            std::process::id(),
+            TEMP_DIR_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        Ok(BulkLoaderPy {
+            opts: options.inner_opt,
+            codec,
+            raw_mode: options.raw_mode,
+            target_file_size,
+            memory_limit,
+            temp_dir,
+            buffer: Vec::new(),
+            buffer_size: 0,
+            runs: Vec::new(),
+            len: 0,
+        })
+    }
+
+    /// Set the codec used to serialize values.
+    ///
+    /// Args:
+    ///     codec (Codec): should be the same as the codec of the target column family.
+    fn set_codec(&mut self, codec: CodecPy) {
+        self.codec = CodecPy {
+            compression: self.codec.compression.take(),
+            ..codec
+        }
+    }
+
+    /// Add a key-value pair, in any order.
+    fn __setitem__(&mut self, key: &PyAny, value: &PyAny) -> PyResult<()> {
+        let key: Box<[u8]> = encode_key(key, self.raw_mode)?.into();
+        let value: Box<[u8]> = encode_value(value, &self.codec, self.raw_mode)?.into();
+        self.buffer_size += key.len() + value.len();
+        self.buffer.push((key, value));
+        self.len += 1;
+        if self.buffer_size >= self.memory_limit {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Add key-value pairs from a dict or from an iterable of `(key, value)` pairs.
+    fn update(&mut self, items: &PyAny) -> PyResult<()> {
+        let items = if items.is_instance_of::<PyDict>() {
+            items.call_method0("items")?
+        } else {
+            items
+        };
+        for item in items.iter()? {
+            let (key, value): (&PyAny, &PyAny) = item?.extract()?;
+            self.__setitem__(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// The number of pairs added (duplicated keys included).
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    /// Build the SST files into `directory`, and reset the loader.
+    ///
+    /// Returns:
+    ///     the paths of the SST files, in key order.
+    fn write(&mut self, directory: PathBuf) -> PyResult<Vec<String>> {
+        self.clear_on_error(|loader| loader.write_files(&directory))
+    }
+
+    /// Build the SST files, ingest them into `db`,
+    /// and reset the loader.
+    ///
+    /// Args:
+    ///     db: the `Rdict` to ingest into (its current column family).
+    ///     opts: IngestExternalFileOptionsPy instance
+    #[pyo3(signature = (
+        db,
+        opts = Python::with_gil(|py| Py::new(py, IngestExternalFileOptionsPy::new()).unwrap())
+    ))]
+    fn ingest(
+        &mut self,
+        db: PyRef<Rdict>,
+        opts: Py<IngestExternalFileOptionsPy>,
+        py: Python,
+    ) -> PyResult<()> {
+        if db.namespace.is_some() {
+            return Err(PyValueError::new_err(
+                "cannot ingest into a namespace, ingest into its column family",
+            ));
+        }
+        if db.opt_py.raw_mode != self.raw_mode {
+            return Err(PyValueError::new_err(
+                "the raw_mode of the BulkLoader and the Rdict must be the same",
+            ));
+        }
+        let directory = self.temp_dir.join("sst");
+        let files = self.clear_on_error(|loader| loader.write_files(&directory))?;
+        let result = if files.is_empty() {
+            Ok(())
+        } else {
+            db.ingest_external_file(files, opts, py)
+        };
+        fs::remove_dir_all(&self.temp_dir).ok();
+        result
+    }
+}
+
+impl BulkLoaderPy {
+    /// run `f`, resetting the loader if it fails
+    fn clear_on_error<T>(&mut self, f: impl FnOnce(&mut Self) -> PyResult<T>) -> PyResult<T> {
+        let result = f(self);
+        if result.is_err() {
+            self.clear().ok();
+        }
+        result
+    }
+}
+
+impl Drop for BulkLoaderPy {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.temp_dir).ok();
+    }
+}
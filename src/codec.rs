@@ -0,0 +1,550 @@
+use crate::exceptions::{CorruptionError, InvalidArgumentError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde::{Deserialize, Serialize};
+
+pub(crate) const PICKLE_CODEC: &str = "pickle";
+pub(crate) const JSON_CODEC: &str = "json";
+pub(crate) const MSGPACK_CODEC: &str = "msgpack";
+
+static PICKLE: GILOnceCell<(PyObject, PyObject)> = GILOnceCell::new();
+
+/// Codecs define how values that are not natively supported
+/// (i.e. not `int`, `float`, `bool`, `str`, `bytes` or `numpy.ndarray`)
+/// are serialized.
+///
+/// Notes:
+///     The codec name of each column family is stored in
+///     `rocksdict-config.json`, so that other processes and languages
+///     know how to decode the data. The built-in `json` and `msgpack`
+///     codecs are implemented in Rust and restored automatically when
+///     the database is reopened; values written with them can always
+///     be read back regardless of the currently configured codec.
+///     Custom codecs must be registered again by calling
+///     `set_codec` after reopening the database.
+///
+///     The built-in codecs raise `InvalidArgumentError` for values they
+///     cannot encode, and `CorruptionError` for data they cannot decode.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, Options, Codec
+///
+///         opt = Options()
+///         opt.set_codec(Codec.json())
+///         db = Rdict("./codec_example", opt)
+///         db["config"] = {"name": "rocksdict", "tags": [1, 2, 3]}
+///
+///         # use msgpack for a column family
+///         cf_opt = Options()
+///         cf_opt.set_codec(Codec.msgpack())
+///         cf = db.create_column_family("msgpack", cf_opt)
+///         cf["blob"] = {"data": b"\x00\x01"}
+///
+///         # python-defined codec
+///         import marshal
+///         db.set_codec(Codec.custom("marshal", marshal.dumps, marshal.loads))
+///
+///         del cf, db
+///         Rdict.destroy("./codec_example")
+#[pyclass(name = "Codec")]
+#[derive(Clone)]
+pub(crate) struct CodecPy {
+    pub(crate) name: String,
+    pub(crate) kind: CodecKind,
+    /// python function used to serialize values (`Pickle` and `Custom` codecs)
+    pub(crate) dumps: Option<PyObject>,
+    /// python function used to deserialize python-serialized values
+    pub(crate) loads: Option<PyObject>,
+    /// value-level compression applied after serialization
+    pub(crate) compression: Option<ValueCompressionType>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CodecKind {
+    Pickle,
+    Json,
+    MsgPack,
+    Custom,
+}
+
+#[pymethods]
+impl CodecPy {
+    /// Serialize values using `pickle` (default).
+    #[staticmethod]
+    pub fn pickle(py: Python) -> PyResult<Self> {
+        let (dumps, loads) = pickle_functions(py)?;
+        Ok(CodecPy {
+            name: PICKLE_CODEC.to_string(),
+            kind: CodecKind::Pickle,
+            dumps: Some(dumps),
+            loads: Some(loads),
+            compression: None,
+        })
+    }
+
+    /// Serialize values as JSON.
+    ///
+    /// Supports `None`, `bool`, `int` (64 bits), `float`, `str`,
+    /// `list`, `tuple` (decoded as `list`) and `dict` with `str` keys.
+    #[staticmethod]
+    pub fn json(py: Python) -> PyResult<Self> {
+        let (_, loads) = pickle_functions(py)?;
+        Ok(CodecPy {
+            name: JSON_CODEC.to_string(),
+            kind: CodecKind::Json,
+            dumps: None,
+            loads: Some(loads),
+            compression: None,
+        })
+    }
+
+    /// Serialize values as MessagePack.
+    ///
+    /// Supports `None`, `bool`, `int` (64 bits), `float`, `str`, `bytes`,
+    /// `list`, `tuple` (decoded as `list`) and `dict`.
+    #[staticmethod]
+    pub fn msgpack(py: Python) -> PyResult<Self> {
+        let (_, loads) = pickle_functions(py)?;
+        Ok(CodecPy {
+            name: MSGPACK_CODEC.to_string(),
+            kind: CodecKind::MsgPack,
+            dumps: None,
+            loads: Some(loads),
+            compression: None,
+        })
+    }
+
+    /// Python-defined codec.
+    ///
+    /// Args:
+    ///     name (str): name of the codec, stored in `rocksdict-config.json`.
+    ///     dumps: function that converts a python object to `bytes`.
+    ///     loads: function that converts `bytes` back to a python object.
+    #[staticmethod]
+    pub fn custom(name: &str, dumps: PyObject, loads: PyObject) -> PyResult<Self> {
+        if [PICKLE_CODEC, JSON_CODEC, MSGPACK_CODEC].contains(&name) {
+            return Err(InvalidArgumentError::new_err(format!(
+                "codec name `{name}` is reserved for built-in codecs"
+            )));
+        }
+        Ok(CodecPy {
+            name: name.to_string(),
+            kind: CodecKind::Custom,
+            dumps: Some(dumps),
+            loads: Some(loads),
+            compression: None,
+        })
+    }
+
+    /// name of this codec
+    #[getter]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Codec({})", self.name)
+    }
+}
+
+impl CodecPy {
+    /// restore a codec from the name stored in `rocksdict-config.json`.
+    ///
+    /// Custom codecs cannot be restored, they are returned without
+    /// functions and raise an exception when used until `set_codec` is called.
+    pub(crate) fn from_name(name: &str, py: Python) -> PyResult<Self> {
+        match name {
+            PICKLE_CODEC => CodecPy::pickle(py),
+            JSON_CODEC => CodecPy::json(py),
+            MSGPACK_CODEC => CodecPy::msgpack(py),
+            _ => Ok(CodecPy {
+                name: name.to_string(),
+                kind: CodecKind::Custom,
+                dumps: None,
+                loads: None,
+                compression: None,
+            }),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn dumps(&self, value: &PyAny) -> PyResult<Vec<u8>> {
+        match &self.dumps {
+            Some(dumps) => dumps.call1(value.py(), (value,))?.extract(value.py()),
+            None => Err(self.unregistered()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn loads(&self, py: Python, bytes: &[u8]) -> PyResult<PyObject> {
+        match &self.loads {
+            Some(loads) => loads.call1(py, (PyBytes::new(py, bytes),)),
+            None => Err(self.unregistered()),
+        }
+    }
+
+    fn unregistered(&self) -> PyErr {
+        InvalidArgumentError::new_err(format!(
+            "codec `{}` is not registered, call `set_codec(Codec.custom(\"{}\", dumps, loads))`",
+            self.name, self.name
+        ))
+    }
+}
+
+fn pickle_functions(py: Python) -> PyResult<(PyObject, PyObject)> {
+    PICKLE
+        .get_or_try_init(py, || {
+            let pickle = PyModule::import(py, "pickle")?;
+            Ok::<_, PyErr>((
+                pickle.getattr("dumps")?.to_object(py),
+                pickle.getattr("loads")?.to_object(py),
+            ))
+        })
+        .map(|(dumps, loads)| (dumps.clone_ref(py), loads.clone_ref(py)))
+}
+
+#[inline]
+fn unsupported(value: &PyAny, format: &str) -> PyErr {
+    let type_name = value.get_type().name().unwrap_or("unknown");
+    InvalidArgumentError::new_err(format!(
+        "Object of type {type_name} is not {format} serializable"
+    ))
+}
+
+/// encode a python object as JSON using `serde_json`
+pub(crate) fn json_dumps(value: &PyAny) -> PyResult<Vec<u8>> {
+    serde_json::to_vec(&py_to_json(value)?)
+        .map_err(|e| InvalidArgumentError::new_err(e.to_string()))
+}
+
+/// decode JSON bytes to python objects
+pub(crate) fn json_loads(py: Python, bytes: &[u8]) -> PyResult<PyObject> {
+    let value: serde_json::Value =
+        serde_json::from_slice(bytes).map_err(|e| CorruptionError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
+
+/// encode a python object as MessagePack using `rmp_serde`
+pub(crate) fn msgpack_dumps(value: &PyAny) -> PyResult<Vec<u8>> {
+    rmp_serde::to_vec(&py_to_msgpack(value)?)
+        .map_err(|e| InvalidArgumentError::new_err(e.to_string()))
+}
+
+/// decode MessagePack bytes to python objects
+pub(crate) fn msgpack_loads(py: Python, bytes: &[u8]) -> PyResult<PyObject> {
+    let value: rmpv::Value =
+        rmp_serde::from_slice(bytes).map_err(|e| CorruptionError::new_err(e.to_string()))?;
+    msgpack_to_py(py, &value)
+}
+
+fn py_to_json(value: &PyAny) -> PyResult<serde_json::Value> {
+    use serde_json::Value;
+    if value.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(value) = <PyBool as PyTryFrom>::try_from(value) {
+        return Ok(Value::Bool(value.is_true()));
+    }
+    if let Ok(int) = <PyInt as PyTryFrom>::try_from(value) {
+        return if let Ok(i) = int.extract::<i64>() {
+            Ok(Value::from(i))
+        } else if let Ok(u) = int.extract::<u64>() {
+            Ok(Value::from(u))
+        } else {
+            Err(InvalidArgumentError::new_err(
+                "int too large for json codec",
+            ))
+        };
+    }
+    if let Ok(float) = <PyFloat as PyTryFrom>::try_from(value) {
+        return serde_json::Number::from_f64(float.value())
+            .map(Value::Number)
+            .ok_or_else(|| {
+                InvalidArgumentError::new_err("json codec does not support nan or inf")
+            });
+    }
+    if let Ok(string) = <PyString as PyTryFrom>::try_from(value) {
+        return Ok(Value::String(string.to_str()?.to_string()));
+    }
+    if let Ok(list) = <PyList as PyTryFrom>::try_from(value) {
+        return list
+            .iter()
+            .map(py_to_json)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+    if let Ok(tuple) = <PyTuple as PyTryFrom>::try_from(value) {
+        return tuple
+            .iter()
+            .map(py_to_json)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+    if let Ok(dict) = <PyDict as PyTryFrom>::try_from(value) {
+        let mut map = serde_json::Map::with_capacity(dict.len());
+        for (k, v) in dict.iter() {
+            let key = match <PyString as PyTryFrom>::try_from(k) {
+                Ok(k) => k.to_str()?.to_string(),
+                Err(_) => {
+                    return Err(InvalidArgumentError::new_err(
+                        "json object keys must be str",
+                    ))
+                }
+            };
+            map.insert(key, py_to_json(v)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    Err(unsupported(value, "JSON"))
+}
+
+fn json_to_py(py: Python, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.to_object(py),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.to_object(py)
+            } else if let Some(u) = n.as_u64() {
+                u.to_object(py)
+            } else {
+                n.as_f64().unwrap_or(f64::NAN).to_object(py)
+            }
+        }
+        Value::String(s) => s.to_object(py),
+        Value::Array(array) => {
+            let list = PyList::empty(py);
+            for v in array {
+                list.append(json_to_py(py, v)?)?;
+            }
+            list.to_object(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(k, json_to_py(py, v)?)?;
+            }
+            dict.to_object(py)
+        }
+    })
+}
+
+fn py_to_msgpack(value: &PyAny) -> PyResult<rmpv::Value> {
+    use rmpv::Value;
+    if value.is_none() {
+        return Ok(Value::Nil);
+    }
+    if let Ok(value) = <PyBool as PyTryFrom>::try_from(value) {
+        return Ok(Value::Boolean(value.is_true()));
+    }
+    if let Ok(int) = <PyInt as PyTryFrom>::try_from(value) {
+        return if let Ok(i) = int.extract::<i64>() {
+            Ok(Value::from(i))
+        } else if let Ok(u) = int.extract::<u64>() {
+            Ok(Value::from(u))
+        } else {
+            Err(InvalidArgumentError::new_err(
+                "int too large for msgpack codec",
+            ))
+        };
+    }
+    if let Ok(float) = <PyFloat as PyTryFrom>::try_from(value) {
+        return Ok(Value::F64(float.value()));
+    }
+    if let Ok(string) = <PyString as PyTryFrom>::try_from(value) {
+        return Ok(Value::from(string.to_str()?));
+    }
+    if let Ok(bytes) = <PyBytes as PyTryFrom>::try_from(value) {
+        return Ok(Value::Binary(bytes.as_bytes().to_vec()));
+    }
+    if let Ok(list) = <PyList as PyTryFrom>::try_from(value) {
+        return list
+            .iter()
+            .map(py_to_msgpack)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+    if let Ok(tuple) = <PyTuple as PyTryFrom>::try_from(value) {
+        return tuple
+            .iter()
+            .map(py_to_msgpack)
+            .collect::<PyResult<_>>()
+            .map(Value::Array);
+    }
+    if let Ok(dict) = <PyDict as PyTryFrom>::try_from(value) {
+        return dict
+            .iter()
+            .map(|(k, v)| Ok((py_to_msgpack(k)?, py_to_msgpack(v)?)))
+            .collect::<PyResult<_>>()
+            .map(Value::Map);
+    }
+    Err(unsupported(value, "MessagePack"))
+}
+
+fn msgpack_to_py(py: Python, value: &rmpv::Value) -> PyResult<PyObject> {
+    use rmpv::Value;
+    Ok(match value {
+        Value::Nil => py.None(),
+        Value::Boolean(b) => b.to_object(py),
+        Value::Integer(n) => {
+            if let Some(i) = n.as_i64() {
+                i.to_object(py)
+            } else {
+                n.as_u64().unwrap_or_default().to_object(py)
+            }
+        }
+        Value::F32(f) => f.to_object(py),
+        Value::F64(f) => f.to_object(py),
+        Value::String(s) => match s.as_str() {
+            Some(s) => s.to_object(py),
+            None => PyBytes::new(py, s.as_bytes()).to_object(py),
+        },
+        Value::Binary(b) => PyBytes::new(py, b).to_object(py),
+        Value::Array(array) => {
+            let list = PyList::empty(py);
+            for v in array {
+                list.append(msgpack_to_py(py, v)?)?;
+            }
+            list.to_object(py)
+        }
+        Value::Map(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(msgpack_to_py(py, k)?, msgpack_to_py(py, v)?)?;
+            }
+            dict.to_object(py)
+        }
+        Value::Ext(..) => {
+            return Err(CorruptionError::new_err(
+                "msgpack extension types are not supported",
+            ))
+        }
+    })
+}
+
+/// Transparent value-level compression.
+///
+/// Values whose encoded size is at least `threshold` bytes are compressed
+/// before being written, and are transparently decompressed when read,
+/// regardless of the compression currently configured. Keys are never
+/// compressed. This is useful for large values (e.g. JSON documents) that
+/// are cached uncompressed by RocksDB.
+///
+/// Notes:
+///     Not available in `raw_mode`. The setting is persisted per column
+///     family in `rocksdict-config.json`.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, Options, ValueCompression
+///
+///         opt = Options()
+///         opt.set_value_compression(ValueCompression.zstd(level=3, threshold=1024))
+///         db = Rdict("./value_compression", opt)
+///         db["doc"] = "x" * 100000
+///
+///         # switch to lz4 for the following writes
+///         db.set_value_compression(ValueCompression.lz4())
+///
+///         del db
+///         Rdict.destroy("./value_compression")
+#[pyclass(name = "ValueCompression")]
+#[derive(Clone)]
+pub(crate) struct ValueCompressionPy(pub(crate) ValueCompressionType);
+
+#[derive(Deserialize, Serialize, Clone)]
+pub enum ValueCompressionType {
+    Zstd { level: i32, threshold: usize },
+    Lz4 { threshold: usize },
+}
+
+/// flag set on the type byte of compressed values
+pub(crate) const COMPRESSED_FLAG: u8 = 0x80;
+const ZSTD_COMPRESSION: u8 = 1;
+const LZ4_COMPRESSION: u8 = 2;
+
+#[pymethods]
+impl ValueCompressionPy {
+    /// zstd compression.
+    ///
+    /// Args:
+    ///     level (int): zstd compression level.
+    ///     threshold (int): minimum size in bytes of values to compress.
+    #[staticmethod]
+    #[pyo3(signature = (level = 3, threshold = 1024))]
+    pub fn zstd(level: i32, threshold: usize) -> Self {
+        ValueCompressionPy(ValueCompressionType::Zstd { level, threshold })
+    }
+
+    /// lz4 compression.
+    ///
+    /// Args:
+    ///     threshold (int): minimum size in bytes of values to compress.
+    #[staticmethod]
+    #[pyo3(signature = (threshold = 1024))]
+    pub fn lz4(threshold: usize) -> Self {
+        ValueCompressionPy(ValueCompressionType::Lz4 { threshold })
+    }
+}
+
+impl ValueCompressionType {
+    ///
+    /// Compress an encoded value `[type byte][payload]` into
+    /// `[type byte | COMPRESSED_FLAG][algorithm byte][compressed payload]`.
+    ///
+    /// Returns `None` if the value is below the threshold or does not shrink.
+    ///
+    pub(crate) fn compress(&self, encoded: &[u8]) -> PyResult<Option<Vec<u8>>> {
+        let (type_byte, payload) = match encoded.split_first() {
+            Some(split) => split,
+            None => return Ok(None),
+        };
+        let (algorithm, compressed) = match self {
+            ValueCompressionType::Zstd { level, threshold } => {
+                if payload.len() < *threshold {
+                    return Ok(None);
+                }
+                let compressed = zstd::bulk::compress(payload, *level)
+                    .map_err(|e| InvalidArgumentError::new_err(e.to_string()))?;
+                (ZSTD_COMPRESSION, compressed)
+            }
+            ValueCompressionType::Lz4 { threshold } => {
+                if payload.len() < *threshold {
+                    return Ok(None);
+                }
+                (LZ4_COMPRESSION, lz4_flex::compress_prepend_size(payload))
+            }
+        };
+        if compressed.len() + 1 >= payload.len() {
+            return Ok(None);
+        }
+        let mut output = Vec::with_capacity(compressed.len() + 2);
+        output.push(type_byte | COMPRESSED_FLAG);
+        output.push(algorithm);
+        output.extend_from_slice(&compressed);
+        Ok(Some(output))
+    }
+}
+
+/// inverse of `ValueCompressionType::compress`, returns `[type byte][payload]`
+pub(crate) fn decompress_value(bytes: &[u8]) -> PyResult<Vec<u8>> {
+    if bytes.len() < 2 {
+        return Err(CorruptionError::new_err("malformed compressed value"));
+    }
+    let payload = match bytes[1] {
+        ZSTD_COMPRESSION => zstd::stream::decode_all(&bytes[2..])
+            .map_err(|e| CorruptionError::new_err(e.to_string()))?,
+        LZ4_COMPRESSION => lz4_flex::decompress_size_prepended(&bytes[2..])
+            .map_err(|e| CorruptionError::new_err(e.to_string()))?,
+        _ => return Err(CorruptionError::new_err("unknown value compression")),
+    };
+    let mut output = Vec::with_capacity(payload.len() + 1);
+    output.push(bytes[0] & !COMPRESSED_FLAG);
+    output.extend_from_slice(&payload);
+    Ok(output)
+}
@@ -1,8 +1,25 @@
+use crate::codec::{
+    decompress_value, json_dumps, json_loads, msgpack_dumps, msgpack_loads, CodecKind, CodecPy,
+    COMPRESSED_FLAG,
+};
+use crate::exceptions::CorruptionError;
 use num_bigint::BigInt;
 use pyo3::exceptions::{PyException, PyKeyError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyBytes, PyFloat, PyInt, PyString};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{
+    IntoPyDict, PyBool, PyByteArray, PyBytes, PyDate, PyDateAccess, PyDateTime, PyDelta,
+    PyDeltaAccess, PyFloat, PyInt, PyString, PyTimeAccess, PyTuple, PyTzInfo,
+};
+use pyo3::{ffi, AsPyPointer};
 use std::borrow::Cow;
+use std::mem::MaybeUninit;
+
+static NUMPY: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+static UUID: GILOnceCell<PyObject> = GILOnceCell::new();
+static DECIMAL: GILOnceCell<PyObject> = GILOnceCell::new();
+static TIMEZONE: GILOnceCell<PyObject> = GILOnceCell::new();
+static ZONE_INFO: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
 
 pub(crate) enum ValueTypes<'a, 'b> {
     Bytes(&'a [u8]),
@@ -11,8 +28,19 @@ pub(crate) enum ValueTypes<'a, 'b> {
     Float(f64),
     Bool(bool),
     Any(&'b PyAny),
+    NdArray(&'b PyAny),
+    None,
+    DateTime(&'b PyDateTime),
+    Date(&'b PyDate),
+    Uuid(&'b PyAny),
+    Decimal(&'b PyAny),
 }
 
+/// type byte of values serialized by the `json` codec
+const JSON_ENCODING: u8 = 8;
+/// type byte of values serialized by the `msgpack` codec
+const MSGPACK_ENCODING: u8 = 9;
+
 #[inline(always)]
 pub(crate) fn encoding_byte(v_type: &ValueTypes) -> u8 {
     match v_type {
@@ -22,6 +50,12 @@ pub(crate) fn encoding_byte(v_type: &ValueTypes) -> u8 {
         ValueTypes::Float(_) => 4,
         ValueTypes::Bool(_) => 5,
         ValueTypes::Any(_) => 6,
+        ValueTypes::NdArray(_) => 7,
+        ValueTypes::None => 10,
+        ValueTypes::DateTime(_) => 11,
+        ValueTypes::Date(_) => 12,
+        ValueTypes::Uuid(_) => 13,
+        ValueTypes::Decimal(_) => 14,
     }
 }
 
@@ -51,8 +85,14 @@ pub(crate) fn encode_key(key: &PyAny, raw_mode: bool) -> PyResult<Cow<[u8]>> {
             type_encoding,
             if value { &[1u8] } else { &[0u8] },
         )),
-        ValueTypes::Any(_) => Err(PyException::new_err(
-            "Only support `string`, `int`, `float`, `bool`, and `bytes` as keys",
+        ValueTypes::None => Ok(vec![type_encoding]),
+        ValueTypes::DateTime(value) => encode_datetime(type_encoding, value, true),
+        ValueTypes::Date(value) => Ok(encode_date(type_encoding, value)),
+        ValueTypes::Uuid(value) => encode_uuid(type_encoding, value),
+        ValueTypes::Decimal(value) => encode_decimal(type_encoding, value, true),
+        ValueTypes::Any(_) | ValueTypes::NdArray(_) => Err(PyException::new_err(
+            "Only support `string`, `int`, `float`, `bool`, `bytes`, `None`, \
+            `datetime`, `date`, `UUID` and `Decimal` as keys",
         )),
     }?;
     Ok(Cow::Owned(owned_bytes))
@@ -61,12 +101,14 @@ pub(crate) fn encode_key(key: &PyAny, raw_mode: bool) -> PyResult<Cow<[u8]>> {
 ///
 /// Convert string, int, float, bytes to byte encodings.
 ///
-/// The first byte is used for encoding value types
+/// The first byte is used for encoding value types,
+/// other values are serialized by `codec`, and the result
+/// is compressed if `codec` has value compression enabled.
 ///
 #[inline(always)]
 pub(crate) fn encode_value<'a>(
     value: &'a PyAny,
-    dumps: &PyObject,
+    codec: &CodecPy,
     raw_mode: bool,
 ) -> PyResult<Cow<'a, [u8]>> {
     if raw_mode {
@@ -90,12 +132,27 @@ pub(crate) fn encode_value<'a>(
             ValueTypes::Bool(value) => {
                 concat_type_encoding(type_encoding, if value { &[1u8] } else { &[0u8] })
             }
-            ValueTypes::Any(value) => {
-                let pickle_bytes: Vec<u8> =
-                    Python::with_gil(|py| dumps.call1(py, (value,))?.extract(py))?;
-                concat_type_encoding(type_encoding, &pickle_bytes[..])
-            }
+            ValueTypes::Any(value) => match codec.kind {
+                CodecKind::Json => concat_type_encoding(JSON_ENCODING, &json_dumps(value)?),
+                CodecKind::MsgPack => {
+                    concat_type_encoding(MSGPACK_ENCODING, &msgpack_dumps(value)?)
+                }
+                CodecKind::Pickle | CodecKind::Custom => {
+                    concat_type_encoding(type_encoding, &codec.dumps(value)?)
+                }
+            },
+            ValueTypes::NdArray(value) => encode_ndarray(type_encoding, value)?,
+            ValueTypes::None => vec![type_encoding],
+            ValueTypes::DateTime(value) => encode_datetime(type_encoding, value, false)?,
+            ValueTypes::Date(value) => encode_date(type_encoding, value),
+            ValueTypes::Uuid(value) => encode_uuid(type_encoding, value)?,
+            ValueTypes::Decimal(value) => encode_decimal(type_encoding, value, false)?,
         };
+        if let Some(compression) = &codec.compression {
+            if let Some(compressed) = compression.compress(&owned_bytes)? {
+                return Ok(Cow::Owned(compressed));
+            }
+        }
         Ok(Cow::Owned(owned_bytes))
     }
 }
@@ -117,15 +174,471 @@ fn py_to_value_types(value: &PyAny) -> PyResult<ValueTypes> {
     if let Ok(value) = <PyFloat as PyTryFrom>::try_from(value) {
         return Ok(ValueTypes::Float(value.extract()?));
     }
+    if value.is_none() {
+        return Ok(ValueTypes::None);
+    }
+    // exact types only, subclasses (e.g. pandas.Timestamp) may carry more information
+    if value.is_exact_instance_of::<PyDateTime>() {
+        return Ok(ValueTypes::DateTime(value.downcast()?));
+    }
+    if value.is_exact_instance_of::<PyDate>() {
+        return Ok(ValueTypes::Date(value.downcast()?));
+    }
+    if is_exact_type(value, "uuid", "UUID")? {
+        return Ok(ValueTypes::Uuid(value));
+    }
+    if is_exact_type(value, "decimal", "Decimal")? {
+        return Ok(ValueTypes::Decimal(value));
+    }
+    if is_plain_ndarray(value)? {
+        return Ok(ValueTypes::NdArray(value));
+    }
     Ok(ValueTypes::Any(value))
 }
 
+#[inline(always)]
+fn is_exact_type(value: &PyAny, module: &str, name: &str) -> PyResult<bool> {
+    let value_type = value.get_type();
+    Ok(
+        value_type.name()? == name
+            && value_type.getattr("__module__")?.extract::<&str>()? == module,
+    )
+}
+
+/// Only exact `numpy.ndarray` instances with a fixed-size, non-structured,
+/// non-object dtype get the native encoding. Subclasses and exotic dtypes
+/// are left to pickle so that they round-trip unchanged.
+#[inline(always)]
+fn is_plain_ndarray(value: &PyAny) -> PyResult<bool> {
+    let value_type = value.get_type();
+    if value_type.name()? != "ndarray" {
+        return Ok(false);
+    }
+    if value_type.getattr("__module__")?.extract::<&str>()? != "numpy" {
+        return Ok(false);
+    }
+    let dtype = value.getattr("dtype")?;
+    Ok(dtype.getattr("fields")?.is_none()
+        && !dtype.getattr("hasobject")?.is_true()?
+        && dtype.getattr("itemsize")?.extract::<usize>()? > 0)
+}
+
+#[inline(always)]
+fn numpy(py: Python) -> PyResult<&PyModule> {
+    NUMPY
+        .get_or_try_init(py, || PyModule::import(py, "numpy").map(|m| m.into()))
+        .map(|m| m.as_ref(py))
+}
+
+///
+/// Encode a numpy array as:
+///
+/// `[type byte][dtype len: u8][dtype str][ndim: u8][shape: ndim * u64 BE][C-order raw buffer]`
+///
+/// where dtype str is numpy's `dtype.str` (e.g. `<f8`), which carries byte order.
+///
+fn encode_ndarray(encoding: u8, array: &PyAny) -> PyResult<Vec<u8>> {
+    let py = array.py();
+    let dtype: String = array.getattr("dtype")?.getattr("str")?.extract()?;
+    let shape: Vec<u64> = array.getattr("shape")?.extract()?;
+    if dtype.len() > u8::MAX as usize || shape.len() > u8::MAX as usize {
+        return Err(PyValueError::new_err(
+            "unsupported numpy array dtype or shape",
+        ));
+    }
+    let array = if array.getattr("flags")?.getattr("c_contiguous")?.is_true()? {
+        array
+    } else {
+        numpy(py)?.call_method1("ascontiguousarray", (array,))?
+    };
+    let mut view = MaybeUninit::<ffi::Py_buffer>::uninit();
+    if unsafe {
+        ffi::PyObject_GetBuffer(array.as_ptr(), view.as_mut_ptr(), ffi::PyBUF_C_CONTIGUOUS)
+    } == -1
+    {
+        return Err(PyErr::fetch(py));
+    }
+    let mut view = unsafe { view.assume_init() };
+    let data = unsafe { std::slice::from_raw_parts(view.buf as *const u8, view.len as usize) };
+    let mut output = Vec::with_capacity(3 + dtype.len() + 8 * shape.len() + data.len());
+    output.push(encoding);
+    output.push(dtype.len() as u8);
+    output.extend_from_slice(dtype.as_bytes());
+    output.push(shape.len() as u8);
+    for dim in shape.iter() {
+        output.extend_from_slice(&dim.to_be_bytes());
+    }
+    output.extend_from_slice(data);
+    unsafe { ffi::PyBuffer_Release(&mut view) };
+    Ok(output)
+}
+
+/// decode numpy arrays encoded by `encode_ndarray` using `numpy.frombuffer`
+fn decode_ndarray(py: Python, payload: &[u8]) -> PyResult<PyObject> {
+    let malformed = || CorruptionError::new_err("malformed numpy array encoding");
+    let (dtype_len, payload) = payload.split_first().ok_or_else(malformed)?;
+    let dtype_len = *dtype_len as usize;
+    if payload.len() < dtype_len + 1 {
+        return Err(malformed());
+    }
+    let dtype = std::str::from_utf8(&payload[..dtype_len]).map_err(|_| malformed())?;
+    let ndim = payload[dtype_len] as usize;
+    let payload = &payload[dtype_len + 1..];
+    if payload.len() < 8 * ndim {
+        return Err(malformed());
+    }
+    let shape = payload[..8 * ndim]
+        .chunks_exact(8)
+        .map(|dim| u64::from_be_bytes(dim.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    // copy into a bytearray so that the returned array is writable
+    let buffer = PyByteArray::new(py, &payload[8 * ndim..]);
+    let array = numpy(py)?.call_method1("frombuffer", (buffer, dtype))?;
+    Ok(array
+        .call_method1("reshape", (PyTuple::new(py, shape),))?
+        .to_object(py))
+}
+
+#[inline(always)]
+fn import_attr<'py>(
+    py: Python<'py>,
+    cell: &'static GILOnceCell<PyObject>,
+    module: &str,
+    attr: &str,
+) -> PyResult<&'py PyAny> {
+    cell.get_or_try_init(py, || {
+        Ok::<_, PyErr>(PyModule::import(py, module)?.getattr(attr)?.to_object(py))
+    })
+    .map(|obj| obj.as_ref(py))
+}
+
+/// flip the sign bit so that signed integers sort correctly as big-endian bytes
+#[inline(always)]
+fn i64_to_sortable(value: i64) -> [u8; 8] {
+    ((value as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+#[inline(always)]
+fn i64_from_sortable(bytes: &[u8]) -> i64 {
+    (u64::from_be_bytes(bytes.try_into().unwrap()) ^ (1 << 63)) as i64
+}
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+/// `date.toordinal()` of 1970-01-01
+const UNIX_EPOCH_ORDINAL: i64 = 719_163;
+
+/// days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// inverse of `days_from_civil`, returns (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[inline(always)]
+fn delta_micros(delta: &PyDelta) -> i64 {
+    (delta.get_days() as i64 * 86_400 + delta.get_seconds() as i64) * 1_000_000
+        + delta.get_microseconds() as i64
+}
+
+/// `zoneinfo.ZoneInfo`, `None` before python 3.9
+fn zone_info_type(py: Python) -> PyResult<Option<&PyAny>> {
+    ZONE_INFO
+        .get_or_try_init(py, || {
+            Ok::<_, PyErr>(match PyModule::import(py, "zoneinfo") {
+                Ok(module) => Some(module.getattr("ZoneInfo")?.to_object(py)),
+                Err(_) => None,
+            })
+        })
+        .map(|zone_info| zone_info.as_ref().map(|z| z.as_ref(py)))
+}
+
+///
+/// Encode a datetime as:
+///
+/// `[type byte][UTC (or naive) microseconds since epoch: sortable i64][0: naive | 1: aware | 2: zoned][utc offset micros: i64 BE][zone key: utf-8]`
+///
+/// Values with a `zoneinfo.ZoneInfo` tzinfo are stored as zoned, with the key
+/// of the zone (e.g. `Europe/Paris`), so that they are decoded with the same zone.
+/// Other tzinfos are decoded as a fixed offset `datetime.timezone`.
+///
+/// Keys are normalized to UTC (offset 0) so that equal instants map to the same key,
+/// and keep neither the offset nor the zone.
+///
+fn encode_datetime(encoding: u8, value: &PyDateTime, as_key: bool) -> PyResult<Vec<u8>> {
+    let days = days_from_civil(
+        value.get_year() as i64,
+        value.get_month() as i64,
+        value.get_day() as i64,
+    );
+    let seconds =
+        value.get_hour() as i64 * 3600 + value.get_minute() as i64 * 60 + value.get_second() as i64;
+    let local = days * MICROS_PER_DAY + seconds * 1_000_000 + value.get_microsecond() as i64;
+    let offset = value.call_method0("utcoffset")?;
+    let mut output = Vec::with_capacity(18);
+    output.push(encoding);
+    if offset.is_none() {
+        output.extend_from_slice(&i64_to_sortable(local));
+        output.push(0);
+    } else {
+        let offset = delta_micros(offset.downcast()?);
+        output.extend_from_slice(&i64_to_sortable(local - offset));
+        let zone_key = match (as_key, zone_info_type(value.py())?) {
+            (false, Some(zone_info)) => {
+                let tzinfo = value.getattr("tzinfo")?;
+                // zones loaded with `ZoneInfo.from_file` have no key
+                match tzinfo.is_instance(zone_info)? {
+                    true => tzinfo.getattr("key")?.extract::<Option<&str>>()?,
+                    false => None,
+                }
+            }
+            _ => None,
+        };
+        output.push(if zone_key.is_some() { 2 } else { 1 });
+        let offset = if as_key { 0 } else { offset };
+        output.extend_from_slice(&offset.to_be_bytes());
+        if let Some(zone_key) = zone_key {
+            output.extend_from_slice(zone_key.as_bytes());
+        }
+    }
+    Ok(output)
+}
+
+fn decode_datetime(py: Python, payload: &[u8]) -> PyResult<PyObject> {
+    let malformed = || CorruptionError::new_err("malformed datetime encoding");
+    if payload.len() < 9 {
+        return Err(malformed());
+    }
+    let utc = i64_from_sortable(&payload[..8]);
+    let (local, tzinfo) = match payload[8] {
+        0 => (utc, None),
+        1 | 2 if payload.len() >= 17 => {
+            let offset = i64::from_be_bytes(payload[9..17].try_into().unwrap());
+            let remainder = offset.rem_euclid(MICROS_PER_DAY);
+            let delta = PyDelta::new(
+                py,
+                offset.div_euclid(MICROS_PER_DAY) as i32,
+                (remainder / 1_000_000) as i32,
+                (remainder % 1_000_000) as i32,
+                true,
+            )?;
+            let tz = import_attr(py, &TIMEZONE, "datetime", "timezone")?.call1((delta,))?;
+            (utc + offset, Some(tz.downcast::<PyTzInfo>()?))
+        }
+        _ => return Err(malformed()),
+    };
+    if payload[8] == 1 && payload.len() != 17 {
+        return Err(malformed());
+    }
+    let (year, month, day) = civil_from_days(local.div_euclid(MICROS_PER_DAY));
+    let micros = local.rem_euclid(MICROS_PER_DAY);
+    let seconds = micros / 1_000_000;
+    let datetime = PyDateTime::new(
+        py,
+        year as i32,
+        month as u8,
+        day as u8,
+        (seconds / 3600) as u8,
+        (seconds % 3600 / 60) as u8,
+        (seconds % 60) as u8,
+        (micros % 1_000_000) as u32,
+        tzinfo,
+    )?;
+    if payload[8] == 2 {
+        let zone_key = std::str::from_utf8(&payload[17..]).map_err(|_| malformed())?;
+        // same instant, in the original zone if it is available,
+        // otherwise with the fixed offset
+        if let Some(Ok(zone)) = zone_info_type(py)?.map(|z| z.call1((zone_key,))) {
+            return Ok(datetime.call_method1("astimezone", (zone,))?.to_object(py));
+        }
+    }
+    Ok(datetime.to_object(py))
+}
+
+/// Encode a date as `[type byte][date.toordinal(): u32 BE]`
+fn encode_date(encoding: u8, value: &PyDate) -> Vec<u8> {
+    let ordinal = days_from_civil(
+        value.get_year() as i64,
+        value.get_month() as i64,
+        value.get_day() as i64,
+    ) + UNIX_EPOCH_ORDINAL;
+    concat_type_encoding(encoding, &(ordinal as u32).to_be_bytes())
+}
+
+fn decode_date(py: Python, payload: &[u8]) -> PyResult<PyObject> {
+    let ordinal = u32::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| CorruptionError::new_err("malformed date encoding"))?,
+    );
+    let (year, month, day) = civil_from_days(ordinal as i64 - UNIX_EPOCH_ORDINAL);
+    Ok(PyDate::new(py, year as i32, month as u8, day as u8)?.to_object(py))
+}
+
+/// Encode a UUID as `[type byte][16 bytes big-endian]`
+fn encode_uuid(encoding: u8, value: &PyAny) -> PyResult<Vec<u8>> {
+    let bytes: &[u8] = value.getattr("bytes")?.extract()?;
+    Ok(concat_type_encoding(encoding, bytes))
+}
+
+fn decode_uuid(py: Python, payload: &[u8]) -> PyResult<PyObject> {
+    let kwargs = [("bytes", PyBytes::new(py, payload))].into_py_dict(py);
+    Ok(import_attr(py, &UUID, "uuid", "UUID")?
+        .call((), Some(kwargs))?
+        .to_object(py))
+}
+
+const DECIMAL_NEG_INF: u8 = 0x01;
+const DECIMAL_NEG: u8 = 0x02;
+const DECIMAL_ZERO: u8 = 0x03;
+const DECIMAL_POS: u8 = 0x04;
+const DECIMAL_POS_INF: u8 = 0x05;
+const DECIMAL_NAN: u8 = 0x06;
+
+///
+/// Encode a Decimal in an order-preserving way:
+///
+/// `[type byte][marker][adjusted exponent: sortable i32][digits as ASCII][0x00]`
+///
+/// where digits have trailing zeros stripped, the marker orders
+/// `-Infinity < negative < zero < positive < Infinity < NaN`, and
+/// exponent and digits are bitwise inverted for negative numbers.
+///
+/// Values additionally append the original exponent (and sign for zero),
+/// so that e.g. `Decimal("1.50")` round-trips exactly. Keys are normalized.
+///
+fn encode_decimal(encoding: u8, value: &PyAny, as_key: bool) -> PyResult<Vec<u8>> {
+    let mut output = vec![encoding];
+    let negative = value.call_method0("is_signed")?.is_true()?;
+    if value.call_method0("is_nan")?.is_true()? {
+        output.push(DECIMAL_NAN);
+        return Ok(output);
+    }
+    if value.call_method0("is_infinite")?.is_true()? {
+        output.push(if negative {
+            DECIMAL_NEG_INF
+        } else {
+            DECIMAL_POS_INF
+        });
+        return Ok(output);
+    }
+    let (_, digits, exponent): (u8, Vec<u8>, i32) = value.call_method0("as_tuple")?.extract()?;
+    let (first, last) = match (
+        digits.iter().position(|d| *d != 0),
+        digits.iter().rposition(|d| *d != 0),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        // zero
+        _ => {
+            output.push(DECIMAL_ZERO);
+            if !as_key {
+                output.extend_from_slice(&exponent.to_be_bytes());
+                output.push(negative as u8);
+            }
+            return Ok(output);
+        }
+    };
+    let significant = &digits[first..=last];
+    let adjusted = exponent + (digits.len() - first) as i32 - 1;
+    let mut body = Vec::with_capacity(significant.len() + 5);
+    body.extend_from_slice(&((adjusted as u32) ^ (1 << 31)).to_be_bytes());
+    body.extend(significant.iter().map(|d| b'0' + d));
+    body.push(0);
+    if negative {
+        output.push(DECIMAL_NEG);
+        output.extend(body.iter().map(|b| !b));
+    } else {
+        output.push(DECIMAL_POS);
+        output.extend_from_slice(&body);
+    }
+    if !as_key {
+        output.extend_from_slice(&exponent.to_be_bytes());
+    }
+    Ok(output)
+}
+
+fn decode_decimal(py: Python, payload: &[u8]) -> PyResult<PyObject> {
+    let malformed = || CorruptionError::new_err("malformed decimal encoding");
+    let decimal = import_attr(py, &DECIMAL, "decimal", "Decimal")?;
+    let (marker, payload) = payload.split_first().ok_or_else(malformed)?;
+    let special = match *marker {
+        DECIMAL_NEG_INF => Some("-Infinity"),
+        DECIMAL_POS_INF => Some("Infinity"),
+        DECIMAL_NAN => Some("NaN"),
+        _ => None,
+    };
+    if let Some(special) = special {
+        return Ok(decimal.call1((special,))?.to_object(py));
+    }
+    let (sign, digits, exponent) = match *marker {
+        DECIMAL_ZERO => match payload.len() {
+            0 => (0u8, vec![0u8], 0i32),
+            5 if payload[4] <= 1 => (
+                payload[4],
+                vec![0u8],
+                i32::from_be_bytes(payload[..4].try_into().unwrap()),
+            ),
+            _ => return Err(malformed()),
+        },
+        DECIMAL_NEG | DECIMAL_POS => {
+            let negative = *marker == DECIMAL_NEG;
+            let body: Vec<u8> = if negative {
+                payload.iter().map(|b| !b).collect()
+            } else {
+                payload.to_vec()
+            };
+            let end = body
+                .iter()
+                .skip(4)
+                .position(|b| *b == 0)
+                .ok_or_else(malformed)?
+                + 4;
+            let adjusted = (u32::from_be_bytes(body[..4].try_into().unwrap()) ^ (1 << 31)) as i32;
+            let digits = &body[4..end];
+            if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                return Err(malformed());
+            }
+            let mut digits: Vec<u8> = digits.iter().map(|d| d - b'0').collect();
+            let mut exponent = adjusted - (digits.len() as i32 - 1);
+            // restore the original exponent of values
+            if let Ok(original) = <[u8; 4]>::try_from(&payload[end + 1..]) {
+                let original = i32::from_be_bytes(original);
+                if original < exponent {
+                    digits.extend(std::iter::repeat(0).take((exponent - original) as usize));
+                    exponent = original;
+                }
+            }
+            (negative as u8, digits, exponent)
+        }
+        _ => return Err(malformed()),
+    };
+    let digits = PyTuple::new(py, digits);
+    Ok(decimal.call1(((sign, digits, exponent),))?.to_object(py))
+}
+
 /// this function is used for decoding value from bytes
 #[inline(always)]
 pub(crate) fn decode_value(
     py: Python,
     bytes: &[u8],
-    loads: &PyObject,
+    codec: &CodecPy,
     raw_mode: bool,
 ) -> PyResult<PyObject> {
     // directly return bytes if raw_mode is true
@@ -134,6 +647,9 @@ pub(crate) fn decode_value(
     }
     match bytes.first() {
         None => Err(PyException::new_err("Unknown value type")),
+        Some(byte) if byte & COMPRESSED_FLAG != 0 => {
+            decode_value(py, &decompress_value(bytes)?, codec, raw_mode)
+        }
         Some(byte) => match byte {
             1 => Ok(PyBytes::new(py, &bytes[1..]).to_object(py)),
             2 => {
@@ -152,7 +668,15 @@ pub(crate) fn decode_value(
                 Ok(float.into_py(py))
             }
             5 => Ok((bytes[1] != 0).to_object(py)),
-            6 => loads.call1(py, (PyBytes::new(py, &bytes[1..]),)),
+            6 => codec.loads(py, &bytes[1..]),
+            7 => decode_ndarray(py, &bytes[1..]),
+            10 => Ok(py.None()),
+            11 => decode_datetime(py, &bytes[1..]),
+            12 => decode_date(py, &bytes[1..]),
+            13 => decode_uuid(py, &bytes[1..]),
+            14 => decode_decimal(py, &bytes[1..]),
+            JSON_ENCODING => json_loads(py, &bytes[1..]),
+            MSGPACK_ENCODING => msgpack_loads(py, &bytes[1..]),
             _ => Err(PyException::new_err("Unknown value type")),
         },
     }
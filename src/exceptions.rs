@@ -1,5 +1,9 @@
+use crate::util::error_message;
+use libc::c_char;
 use pyo3::create_exception;
 use pyo3::exceptions::PyException;
+use pyo3::PyErr;
+use rocksdb::ErrorKind;
 
 create_exception!(
     rocksdict,
@@ -7,3 +11,127 @@ create_exception!(
     PyException,
     "Raised when accessing a closed database instance."
 );
+
+create_exception!(
+    rocksdict,
+    RocksDBError,
+    PyException,
+    "Base class of all errors reported by RocksDB."
+);
+
+create_exception!(
+    rocksdict,
+    CorruptionError,
+    RocksDBError,
+    "Raised when RocksDB detects corrupted data."
+);
+
+create_exception!(
+    rocksdict,
+    RocksIOError,
+    RocksDBError,
+    "Raised when RocksDB fails to read or write files (named so as not to shadow the builtin `IOError`)."
+);
+
+create_exception!(
+    rocksdict,
+    LockError,
+    RocksIOError,
+    "Raised when the database lock is held by another process or instance."
+);
+
+create_exception!(
+    rocksdict,
+    BusyError,
+    RocksDBError,
+    "Raised when a resource is busy, the operation may succeed if retried."
+);
+
+create_exception!(
+    rocksdict,
+    TimedOutError,
+    RocksDBError,
+    "Raised when an operation timed out, the operation may succeed if retried."
+);
+
+create_exception!(
+    rocksdict,
+    TryAgainError,
+    RocksDBError,
+    "Raised when an operation failed transiently, the operation may succeed if retried."
+);
+
+create_exception!(
+    rocksdict,
+    NotSupportedError,
+    RocksDBError,
+    "Raised when an operation is not supported."
+);
+
+create_exception!(
+    rocksdict,
+    InvalidArgumentError,
+    RocksDBError,
+    "Raised when RocksDB receives an invalid argument or option."
+);
+
+create_exception!(
+    rocksdict,
+    IncompleteError,
+    RocksDBError,
+    "Raised when an operation could not complete, e.g. with `no_slowdown` writes."
+);
+
+/// convert a `rocksdb::Error` into the python exception of its status code
+pub(crate) fn rocksdb_error(e: rocksdb::Error) -> PyErr {
+    let kind = e.kind();
+    exception_of_kind(kind, e.into_string())
+}
+
+/// convert an error string returned by the rocksdb C API into a python exception
+pub(crate) fn ffi_error(err: *const c_char) -> PyErr {
+    let message = error_message(err);
+    exception_of_kind(error_kind(&message), message)
+}
+
+/// status code of an error message, the same way as `rocksdb::Error::kind`
+fn error_kind(message: &str) -> ErrorKind {
+    match message.split(':').next().unwrap_or("") {
+        "NotFound" => ErrorKind::NotFound,
+        "Corruption" => ErrorKind::Corruption,
+        "Not implemented" => ErrorKind::NotSupported,
+        "Invalid argument" => ErrorKind::InvalidArgument,
+        "IO error" => ErrorKind::IOError,
+        "Merge in progress" => ErrorKind::MergeInProgress,
+        "Result incomplete" => ErrorKind::Incomplete,
+        "Shutdown in progress" => ErrorKind::ShutdownInProgress,
+        "Operation timed out" => ErrorKind::TimedOut,
+        "Operation aborted" => ErrorKind::Aborted,
+        "Resource busy" => ErrorKind::Busy,
+        "Operation expired" => ErrorKind::Expired,
+        "Operation failed. Try again." => ErrorKind::TryAgain,
+        "Compaction too large" => ErrorKind::CompactionTooLarge,
+        "Column family dropped" => ErrorKind::ColumnFamilyDropped,
+        _ => ErrorKind::Unknown,
+    }
+}
+
+fn exception_of_kind(kind: ErrorKind, message: String) -> PyErr {
+    match kind {
+        ErrorKind::Corruption => CorruptionError::new_err(message),
+        ErrorKind::IOError if is_lock_error(&message) => LockError::new_err(message),
+        ErrorKind::IOError => RocksIOError::new_err(message),
+        ErrorKind::Busy => BusyError::new_err(message),
+        ErrorKind::TimedOut => TimedOutError::new_err(message),
+        ErrorKind::TryAgain => TryAgainError::new_err(message),
+        ErrorKind::NotSupported => NotSupportedError::new_err(message),
+        ErrorKind::InvalidArgument => InvalidArgumentError::new_err(message),
+        ErrorKind::Incomplete => IncompleteError::new_err(message),
+        _ => RocksDBError::new_err(message),
+    }
+}
+
+/// RocksDB reports a held `LOCK` file as an IO error
+fn is_lock_error(message: &str) -> bool {
+    message.contains("While lock file") || message.contains("lock hold by current process")
+}
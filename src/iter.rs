@@ -1,13 +1,15 @@
+use crate::codec::CodecPy;
 use crate::db_reference::DbReferenceHolder;
 use crate::encoder::{decode_value, encode_key};
-use crate::exceptions::DbClosedError;
-use crate::util::error_message;
-use crate::{ReadOpt, ReadOptionsPy};
+use crate::exceptions::{ffi_error, DbClosedError};
+use crate::namespace::KeyNamespace;
+use crate::snapshot::set_snapshot;
+use crate::{ReadOpt, ReadOptionsPy, Snapshot};
 use core::slice;
 use libc::{c_char, c_uchar, size_t};
-use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
 use rocksdb::{AsColumnFamilyRef, UnboundColumnFamily};
+use std::borrow::Cow;
 use std::ptr::null_mut;
 use std::sync::Arc;
 
@@ -24,10 +26,16 @@ pub(crate) struct RdictIter {
     /// iterator is being used.
     pub(crate) readopts: ReadOpt,
 
-    /// use pickle loads to convert bytes to pyobjects
-    pub(crate) pickle_loads: PyObject,
+    /// use codec to convert bytes to pyobjects
+    pub(crate) codec: CodecPy,
 
     pub(crate) raw_mode: bool,
+
+    /// keeps the snapshot (if any) alive while the iterator reads from it.
+    pub(crate) snapshot: Option<Py<Snapshot>>,
+
+    /// prepended to the keys to seek, and stripped from the keys read.
+    pub(crate) namespace: Option<KeyNamespace>,
 }
 
 #[pyclass]
@@ -49,15 +57,24 @@ pub(crate) struct RdictValues {
 }
 
 impl RdictIter {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         db: &DbReferenceHolder,
         cf: &Option<Arc<UnboundColumnFamily>>,
         readopts: ReadOptionsPy,
-        pickle_loads: &PyObject,
+        codec: &CodecPy,
         raw_mode: bool,
+        namespaces: bool,
+        namespace: Option<KeyNamespace>,
+        snapshot: Option<Py<Snapshot>>,
         py: Python,
     ) -> PyResult<Self> {
-        let readopts = readopts.to_read_opt(raw_mode, py)?;
+        let readopts = readopts.to_read_opt(raw_mode, namespaces, namespace.as_ref(), py)?;
+        if let Some(snapshot) = &snapshot {
+            unsafe {
+                set_snapshot(readopts.0, snapshot.borrow(py).inner);
+            }
+        }
 
         let db_inner = db
             .get()
@@ -75,8 +92,10 @@ impl RdictIter {
                 }
             },
             readopts,
-            pickle_loads: pickle_loads.clone(),
+            codec: codec.clone(),
             raw_mode,
+            snapshot,
+            namespace,
         })
     }
 }
@@ -105,7 +124,7 @@ impl RdictIter {
             librocksdb_sys::rocksdb_iter_get_error(self.inner, &mut err);
         }
         if !err.is_null() {
-            Err(PyException::new_err(error_message(err)))
+            Err(ffi_error(err))
         } else {
             Ok(())
         }
@@ -166,6 +185,16 @@ impl RdictIter {
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
     pub fn seek_to_last(&mut self) {
+        // with `prefix_same_as_start`, the seek target must be within the namespace
+        if let Some(last) = self
+            .namespace
+            .as_ref()
+            .filter(|ns| ns.prefix_same_as_start())
+            .and_then(|ns| ns.last_key())
+        {
+            self.seek_raw_for_prev(&last);
+            return;
+        }
         unsafe {
             librocksdb_sys::rocksdb_iter_seek_to_last(self.inner);
         }
@@ -192,7 +221,7 @@ impl RdictIter {
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
     pub fn seek(&mut self, key: &PyAny) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
+        let key = self.encode_key(key)?;
         unsafe {
             librocksdb_sys::rocksdb_iter_seek(
                 self.inner,
@@ -225,14 +254,8 @@ impl RdictIter {
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
     pub fn seek_for_prev(&mut self, key: &PyAny) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
-        unsafe {
-            librocksdb_sys::rocksdb_iter_seek_for_prev(
-                self.inner,
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-            );
-        }
+        let key = self.encode_key(key)?;
+        self.seek_raw_for_prev(&key);
         Ok(())
     }
 
@@ -260,8 +283,11 @@ impl RdictIter {
                 let key_len_ptr: *mut size_t = &mut key_len;
                 let key_ptr =
                     librocksdb_sys::rocksdb_iter_key(self.inner, key_len_ptr) as *const c_uchar;
-                let key = slice::from_raw_parts(key_ptr, key_len);
-                Ok(decode_value(py, key, &self.pickle_loads, self.raw_mode)?)
+                let mut key = slice::from_raw_parts(key_ptr, key_len);
+                if let Some(ns) = &self.namespace {
+                    key = ns.strip(key);
+                }
+                Ok(decode_value(py, key, &self.codec, self.raw_mode)?)
             }
         } else {
             Ok(py.None())
@@ -279,7 +305,7 @@ impl RdictIter {
                 let val_ptr =
                     librocksdb_sys::rocksdb_iter_value(self.inner, val_len_ptr) as *const c_uchar;
                 let value = slice::from_raw_parts(val_ptr, val_len);
-                Ok(decode_value(py, value, &self.pickle_loads, self.raw_mode)?)
+                Ok(decode_value(py, value, &self.codec, self.raw_mode)?)
             }
         } else {
             Ok(py.None())
@@ -287,6 +313,40 @@ impl RdictIter {
     }
 }
 
+impl RdictIter {
+    /// encode a key, within the namespace if any.
+    fn encode_key<'a>(&self, key: &'a PyAny) -> PyResult<Cow<'a, [u8]>> {
+        let key = encode_key(key, self.raw_mode)?;
+        Ok(match &self.namespace {
+            None => key,
+            Some(ns) => Cow::Owned(ns.key(&key)),
+        })
+    }
+
+    fn seek_raw_for_prev(&mut self, key: &[u8]) {
+        unsafe {
+            librocksdb_sys::rocksdb_iter_seek_for_prev(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+    }
+
+    /// The current key, not decoded (including the namespace, if any).
+    pub(crate) fn raw_key(&self) -> Option<Vec<u8>> {
+        if self.valid() {
+            unsafe {
+                let mut key_len: size_t = 0;
+                let key_ptr = librocksdb_sys::rocksdb_iter_key(self.inner, &mut key_len);
+                Some(slice::from_raw_parts(key_ptr as *const c_uchar, key_len).to_vec())
+            }
+        } else {
+            None
+        }
+    }
+}
+
 impl Drop for RdictIter {
     fn drop(&mut self) {
         unsafe {
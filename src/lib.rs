@@ -1,18 +1,26 @@
 // #![feature(core_intrinsics)]
+mod bulk_loader;
+mod codec;
 mod db_reference;
 mod encoder;
 mod exceptions;
 mod iter;
+mod namespace;
+mod option_values;
 mod options;
+mod range;
 mod rdict;
 mod snapshot;
 mod sst_file_writer;
 mod util;
 mod write_batch;
 
+use crate::bulk_loader::BulkLoaderPy;
+use crate::codec::{CodecPy, ValueCompressionPy};
 use crate::exceptions::*;
 use crate::iter::*;
 use crate::options::*;
+use crate::range::RdictRange;
 use crate::rdict::*;
 use crate::snapshot::Snapshot;
 use crate::sst_file_writer::*;
@@ -102,9 +110,15 @@ use pyo3::prelude::*;
 ///
 /// Supported types:
 ///
-/// - key: `int, float, bool, str, bytes`
-/// - value: `int, float, bool, str, bytes` and anything that
-///     supports `pickle`.
+/// - key: `int, float, bool, str, bytes, None, datetime, date, UUID, Decimal`
+/// - value: `int, float, bool, str, bytes, None, datetime, date, UUID, Decimal`,
+///     `numpy.ndarray` (stored as
+///     dtype, shape and raw buffer) and anything that supports `pickle`
+///     (or the `Codec` configured with `set_codec`, e.g. json and msgpack).
+///
+/// Aware `datetime` values keep their `zoneinfo.ZoneInfo` zone, other
+/// tzinfos are read back as a fixed offset `datetime.timezone`.
+/// `datetime` keys are normalized to UTC.
 ///
 #[pymodule]
 fn rocksdict(py: Python, m: &PyModule) -> PyResult<()> {
@@ -115,6 +129,7 @@ fn rocksdict(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<CuckooTableOptionsPy>()?;
     m.add_class::<PlainTableFactoryOptionsPy>()?;
     m.add_class::<CachePy>()?;
+    m.add_class::<WriteBufferManagerPy>()?;
     m.add_class::<BlockBasedIndexTypePy>()?;
     m.add_class::<DataBlockIndexTypePy>()?;
     m.add_class::<SliceTransformPy>()?;
@@ -124,6 +139,7 @@ fn rocksdict(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<ReadOptionsPy>()?;
     m.add_class::<DBCompressionTypePy>()?;
     m.add_class::<DBCompactionStylePy>()?;
+    m.add_class::<DBCompactionPriPy>()?;
     m.add_class::<DBRecoveryModePy>()?;
     m.add_class::<UniversalCompactOptionsPy>()?;
     m.add_class::<UniversalCompactionStopStylePy>()?;
@@ -133,8 +149,10 @@ fn rocksdict(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<RdictItems>()?;
     m.add_class::<RdictValues>()?;
     m.add_class::<RdictKeys>()?;
+    m.add_class::<RdictRange>()?;
     m.add_class::<IngestExternalFileOptionsPy>()?;
     m.add_class::<SstFileWriterPy>()?;
+    m.add_class::<BulkLoaderPy>()?;
     m.add_class::<WriteBatchPy>()?;
     m.add_class::<ColumnFamilyPy>()?;
     m.add_class::<AccessType>()?;
@@ -142,9 +160,25 @@ fn rocksdict(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<CompactOptionsPy>()?;
     m.add_class::<BottommostLevelCompactionPy>()?;
     m.add_class::<ChecksumTypePy>()?;
+    m.add_class::<PinningTierPy>()?;
     m.add_class::<KeyEncodingTypePy>()?;
+    m.add_class::<CodecPy>()?;
+    m.add_class::<ValueCompressionPy>()?;
 
     m.add("DbClosedError", py.get_type::<DbClosedError>())?;
+    m.add("RocksDBError", py.get_type::<RocksDBError>())?;
+    m.add("CorruptionError", py.get_type::<CorruptionError>())?;
+    m.add("RocksIOError", py.get_type::<RocksIOError>())?;
+    m.add("LockError", py.get_type::<LockError>())?;
+    m.add("BusyError", py.get_type::<BusyError>())?;
+    m.add("TimedOutError", py.get_type::<TimedOutError>())?;
+    m.add("TryAgainError", py.get_type::<TryAgainError>())?;
+    m.add("NotSupportedError", py.get_type::<NotSupportedError>())?;
+    m.add(
+        "InvalidArgumentError",
+        py.get_type::<InvalidArgumentError>(),
+    )?;
+    m.add("IncompleteError", py.get_type::<IncompleteError>())?;
 
     pyo3_log::init();
     Ok(())
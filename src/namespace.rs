@@ -0,0 +1,178 @@
+use crate::options::SliceTransformType;
+use num_bigint::BigInt;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use std::cmp::Ordering;
+
+/// First byte of namespaced keys (not in raw mode).
+///
+/// No other encoded key starts with this byte, so the keys of
+/// all namespaces sort after the keys outside of any namespace.
+pub(crate) const NAMESPACE_TAG: u8 = 0xFF;
+
+/// The key prefix of a namespace (see `Rdict.namespace`).
+///
+/// In raw mode, the prefix is prepended to the keys as is. Otherwise,
+/// namespaced keys are `NAMESPACE_TAG`, the length of the prefix
+/// (u16 big endian), the prefix, and then the encoded key.
+#[derive(Clone)]
+pub(crate) struct KeyNamespace {
+    prefix: Box<[u8]>,
+    header: Box<[u8]>,
+    raw_mode: bool,
+    prefix_same_as_start: bool,
+}
+
+impl KeyNamespace {
+    pub(crate) fn new(
+        prefix: &PyAny,
+        raw_mode: bool,
+        prefix_extractor: Option<&SliceTransformType>,
+    ) -> PyResult<Self> {
+        let prefix: Box<[u8]> = if let Ok(prefix) = prefix.downcast::<PyString>() {
+            prefix.to_str()?.as_bytes().into()
+        } else if let Ok(prefix) = prefix.downcast::<PyBytes>() {
+            prefix.as_bytes().into()
+        } else {
+            return Err(PyTypeError::new_err(
+                "namespace prefix must be str or bytes",
+            ));
+        };
+        let header: Box<[u8]> = if raw_mode {
+            prefix.clone()
+        } else {
+            let len = u16::try_from(prefix.len()).map_err(|_| {
+                PyValueError::new_err(format!("namespace prefix longer than {} bytes", u16::MAX))
+            })?;
+            let mut header = Vec::with_capacity(prefix.len() + 3);
+            header.push(NAMESPACE_TAG);
+            header.extend_from_slice(&len.to_be_bytes());
+            header.extend_from_slice(&prefix);
+            header.into()
+        };
+        // the prefix extractor can only be used to stay within the namespace if
+        // it extracts exactly the header from every key of the namespace, and if
+        // there is a known key greater than all the keys of the namespace to seek
+        // to for backward iteration. Namespaced keys are never shorter than the
+        // header, so a fixed or capped prefix of its length does.
+        let prefix_same_as_start = !raw_mode
+            && match prefix_extractor {
+                Some(SliceTransformType::Fixed(len)) => *len == header.len(),
+                Some(SliceTransformType::MaxLen(len)) => *len == header.len(),
+                Some(SliceTransformType::Noop) | None => false,
+            };
+        Ok(KeyNamespace {
+            prefix,
+            header,
+            raw_mode,
+            prefix_same_as_start,
+        })
+    }
+
+    /// The prefix as given by the user.
+    pub(crate) fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// The bytes prepended to the encoded keys.
+    pub(crate) fn header(&self) -> &[u8] {
+        &self.header
+    }
+
+    /// Whether iterators can use `prefix_same_as_start` to stay within the namespace.
+    pub(crate) fn prefix_same_as_start(&self) -> bool {
+        self.prefix_same_as_start
+    }
+
+    /// Prepend the header to an encoded key.
+    pub(crate) fn key(&self, encoded: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.header.len() + encoded.len());
+        key.extend_from_slice(&self.header);
+        key.extend_from_slice(encoded);
+        key
+    }
+
+    /// Strip the header from a namespaced key.
+    pub(crate) fn strip<'a>(&self, key: &'a [u8]) -> &'a [u8] {
+        key.strip_prefix(&*self.header).unwrap_or(key)
+    }
+
+    /// The smallest key after all the keys of the namespace,
+    /// `None` if there is no such key.
+    pub(crate) fn upper_bound(&self) -> Option<Vec<u8>> {
+        let mut bound = self.header.to_vec();
+        while let Some(last) = bound.pop() {
+            if last < u8::MAX {
+                bound.push(last + 1);
+                return Some(bound);
+            }
+        }
+        None
+    }
+
+    /// A key within the namespace greater than all the keys of
+    /// the namespace, to seek to the last key with `prefix_same_as_start`.
+    pub(crate) fn last_key(&self) -> Option<Vec<u8>> {
+        if self.raw_mode {
+            None
+        } else {
+            Some(self.key(&[u8::MAX]))
+        }
+    }
+}
+
+/// The upper bound of the keys outside of any namespace in non-raw DBs
+/// with namespaces enabled, so that iterating over a `Rdict` does not see
+/// namespaced keys.
+pub(crate) fn root_upper_bound(namespaces: bool) -> Option<Vec<u8>> {
+    if namespaces {
+        Some(vec![NAMESPACE_TAG])
+    } else {
+        None
+    }
+}
+
+/// The ordering of the `rocksdict` and `rocksdict.v2` comparators,
+/// which only differ in name: the `rocksdict.v2` name is used when namespaces
+/// are enabled, because rocksdict versions without namespaces order namespaced
+/// keys differently.
+///
+/// Int keys are compared by value, namespaced keys are compared
+/// by namespace first, everything else is compared bytewise.
+pub(crate) fn compare_keys(v1: &[u8], v2: &[u8]) -> Ordering {
+    match (v1.first(), v2.first()) {
+        (Some(3), Some(3)) => {
+            BigInt::from_signed_bytes_be(&v1[1..]).cmp(&BigInt::from_signed_bytes_be(&v2[1..]))
+        }
+        (Some(&NAMESPACE_TAG), Some(&NAMESPACE_TAG)) => {
+            match (header_len(v1), header_len(v2)) {
+                (Some(len1), Some(len2)) => v1[..len1].cmp(&v2[..len2]).then_with(|| {
+                    match (v1[len1..].first(), v2[len2..].first()) {
+                        // namespaces cannot be nested
+                        (Some(&NAMESPACE_TAG), _) | (_, Some(&NAMESPACE_TAG)) => {
+                            v1[len1..].cmp(&v2[len2..])
+                        }
+                        _ => compare_keys(&v1[len1..], &v2[len2..]),
+                    }
+                }),
+                _ => v1.cmp(v2),
+            }
+        }
+        _ => v1.cmp(v2),
+    }
+}
+
+/// length of the namespace header of a namespaced key, `None` if truncated.
+#[inline]
+fn header_len(key: &[u8]) -> Option<usize> {
+    if key.len() < 3 {
+        return None;
+    }
+    let len = u16::from_be_bytes([key[1], key[2]]) as usize + 3;
+    if key.len() < len {
+        None
+    } else {
+        Some(len)
+    }
+}
@@ -0,0 +1,771 @@
+use libc::c_int;
+use pyo3::prelude::*;
+use rocksdb::Options;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Whether an option is a DB option or a column family option.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OptionScope {
+    Db,
+    Cf,
+}
+
+/// The value of an option, as read back from `rocksdb_options_t`.
+pub(crate) enum OptionValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    /// the name of an enum value in RocksDB option strings
+    Enum(&'static str),
+}
+
+impl ToPyObject for OptionValue {
+    fn to_object(&self, py: Python) -> PyObject {
+        match self {
+            OptionValue::Bool(v) => v.to_object(py),
+            OptionValue::Int(v) => v.to_object(py),
+            OptionValue::UInt(v) => v.to_object(py),
+            OptionValue::Float(v) => v.to_object(py),
+            OptionValue::Enum(v) => v.to_object(py),
+        }
+    }
+}
+
+impl OptionValue {
+    /// whether `persisted` (as written in an OPTIONS file) is the same value
+    pub(crate) fn matches(&self, persisted: &str) -> bool {
+        match self {
+            // OPTIONS files only keep 6 decimals of doubles
+            OptionValue::Float(v) => persisted
+                .parse::<f64>()
+                .map_or(false, |p| (p - v).abs() <= 1e-6 * v.abs().max(1.0)),
+            _ => self.to_string() == persisted,
+        }
+    }
+
+    /// `persisted` (as written in an OPTIONS file) with the type of this value
+    pub(crate) fn persisted_object(&self, persisted: &str, py: Python) -> PyObject {
+        let parsed = match self {
+            OptionValue::Bool(_) => persisted.parse::<bool>().ok().map(|v| v.to_object(py)),
+            OptionValue::Int(_) => persisted.parse::<i64>().ok().map(|v| v.to_object(py)),
+            OptionValue::UInt(_) => persisted.parse::<u64>().ok().map(|v| v.to_object(py)),
+            OptionValue::Float(_) => persisted.parse::<f64>().ok().map(|v| v.to_object(py)),
+            OptionValue::Enum(_) => None,
+        };
+        parsed.unwrap_or_else(|| persisted.to_object(py))
+    }
+}
+
+/// formatted as in RocksDB option strings
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionValue::Bool(v) => write!(f, "{v}"),
+            OptionValue::Int(v) => write!(f, "{v}"),
+            OptionValue::UInt(v) => write!(f, "{v}"),
+            OptionValue::Float(v) => write!(f, "{v}"),
+            OptionValue::Enum(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+fn compression_name(v: c_int) -> &'static str {
+    match v {
+        0 => "kNoCompression",
+        1 => "kSnappyCompression",
+        2 => "kZlibCompression",
+        3 => "kBZip2Compression",
+        4 => "kLZ4Compression",
+        5 => "kLZ4HCCompression",
+        6 => "kXpressCompression",
+        7 => "kZSTD",
+        _ => "kDisableCompressionOption",
+    }
+}
+
+fn compaction_style_name(v: c_int) -> &'static str {
+    match v {
+        0 => "kCompactionStyleLevel",
+        1 => "kCompactionStyleUniversal",
+        2 => "kCompactionStyleFIFO",
+        _ => "kCompactionStyleNone",
+    }
+}
+
+fn wal_recovery_mode_name(v: c_int) -> &'static str {
+    match v {
+        0 => "kTolerateCorruptedTailRecords",
+        1 => "kAbsoluteConsistency",
+        2 => "kPointInTimeRecovery",
+        _ => "kSkipAnyCorruptedRecords",
+    }
+}
+
+fn info_log_level_name(v: c_int) -> &'static str {
+    match v {
+        0 => "DEBUG_LEVEL",
+        1 => "INFO_LEVEL",
+        2 => "WARN_LEVEL",
+        3 => "ERROR_LEVEL",
+        4 => "FATAL_LEVEL",
+        _ => "HEADER_LEVEL",
+    }
+}
+
+fn prepopulate_blob_cache_name(v: c_int) -> &'static str {
+    match v {
+        0 => "kDisable",
+        _ => "kFlushOnly",
+    }
+}
+
+macro_rules! option_value {
+    (Bool, $v:expr) => {
+        OptionValue::Bool($v != 0)
+    };
+    (Int, $v:expr) => {
+        OptionValue::Int($v as i64)
+    };
+    (UInt, $v:expr) => {
+        OptionValue::UInt($v as u64)
+    };
+    (Float, $v:expr) => {
+        OptionValue::Float($v as f64)
+    };
+    ($name:ident, $v:expr) => {
+        OptionValue::Enum($name($v as c_int))
+    };
+}
+
+macro_rules! option_values {
+    ($opt:expr, $( ($scope:ident, $kind:ident, $name:literal, $getter:ident) ),* $(,)?) => {
+        vec![$(
+            (
+                $name,
+                OptionScope::$scope,
+                option_value!($kind, unsafe { librocksdb_sys::$getter($opt) }),
+            ),
+        )*]
+    };
+}
+
+/// The values of the options that can be read back from `Options`,
+/// named as in RocksDB option strings and OPTIONS files.
+///
+/// Pointer options (table factory, caches, comparator, prefix extractor, ...)
+/// cannot be read back and are not included.
+pub(crate) fn option_values(opt: &Options) -> Vec<(&'static str, OptionScope, OptionValue)> {
+    let opt = opt.inner();
+    option_values!(
+        opt,
+        // DB options
+        (
+            Db,
+            Bool,
+            "create_if_missing",
+            rocksdb_options_get_create_if_missing
+        ),
+        (
+            Db,
+            Bool,
+            "create_missing_column_families",
+            rocksdb_options_get_create_missing_column_families
+        ),
+        (
+            Db,
+            Bool,
+            "error_if_exists",
+            rocksdb_options_get_error_if_exists
+        ),
+        (
+            Db,
+            Bool,
+            "paranoid_checks",
+            rocksdb_options_get_paranoid_checks
+        ),
+        (
+            Db,
+            info_log_level_name,
+            "info_log_level",
+            rocksdb_options_get_info_log_level
+        ),
+        (
+            Db,
+            UInt,
+            "db_write_buffer_size",
+            rocksdb_options_get_db_write_buffer_size
+        ),
+        (
+            Db,
+            Int,
+            "max_open_files",
+            rocksdb_options_get_max_open_files
+        ),
+        (
+            Db,
+            Int,
+            "max_file_opening_threads",
+            rocksdb_options_get_max_file_opening_threads
+        ),
+        (
+            Db,
+            UInt,
+            "max_total_wal_size",
+            rocksdb_options_get_max_total_wal_size
+        ),
+        (
+            Db,
+            UInt,
+            "max_subcompactions",
+            rocksdb_options_get_max_subcompactions
+        ),
+        (
+            Db,
+            Int,
+            "max_background_jobs",
+            rocksdb_options_get_max_background_jobs
+        ),
+        (
+            Db,
+            Int,
+            "max_background_compactions",
+            rocksdb_options_get_max_background_compactions
+        ),
+        (
+            Db,
+            Int,
+            "max_background_flushes",
+            rocksdb_options_get_max_background_flushes
+        ),
+        (
+            Db,
+            UInt,
+            "max_log_file_size",
+            rocksdb_options_get_max_log_file_size
+        ),
+        (
+            Db,
+            UInt,
+            "log_file_time_to_roll",
+            rocksdb_options_get_log_file_time_to_roll
+        ),
+        (
+            Db,
+            UInt,
+            "keep_log_file_num",
+            rocksdb_options_get_keep_log_file_num
+        ),
+        (
+            Db,
+            UInt,
+            "recycle_log_file_num",
+            rocksdb_options_get_recycle_log_file_num
+        ),
+        (
+            Db,
+            UInt,
+            "max_manifest_file_size",
+            rocksdb_options_get_max_manifest_file_size
+        ),
+        (
+            Db,
+            Int,
+            "table_cache_numshardbits",
+            rocksdb_options_get_table_cache_numshardbits
+        ),
+        (Db, Bool, "use_fsync", rocksdb_options_get_use_fsync),
+        (
+            Db,
+            UInt,
+            "manifest_preallocation_size",
+            rocksdb_options_get_manifest_preallocation_size
+        ),
+        (
+            Db,
+            Bool,
+            "allow_mmap_reads",
+            rocksdb_options_get_allow_mmap_reads
+        ),
+        (
+            Db,
+            Bool,
+            "allow_mmap_writes",
+            rocksdb_options_get_allow_mmap_writes
+        ),
+        (
+            Db,
+            Bool,
+            "use_direct_reads",
+            rocksdb_options_get_use_direct_reads
+        ),
+        (
+            Db,
+            Bool,
+            "use_direct_io_for_flush_and_compaction",
+            rocksdb_options_get_use_direct_io_for_flush_and_compaction
+        ),
+        (
+            Db,
+            Bool,
+            "is_fd_close_on_exec",
+            rocksdb_options_get_is_fd_close_on_exec
+        ),
+        (
+            Db,
+            UInt,
+            "stats_dump_period_sec",
+            rocksdb_options_get_stats_dump_period_sec
+        ),
+        (
+            Db,
+            UInt,
+            "stats_persist_period_sec",
+            rocksdb_options_get_stats_persist_period_sec
+        ),
+        (
+            Db,
+            Bool,
+            "advise_random_on_open",
+            rocksdb_options_get_advise_random_on_open
+        ),
+        (
+            Db,
+            Bool,
+            "use_adaptive_mutex",
+            rocksdb_options_get_use_adaptive_mutex
+        ),
+        (
+            Db,
+            UInt,
+            "bytes_per_sync",
+            rocksdb_options_get_bytes_per_sync
+        ),
+        (
+            Db,
+            UInt,
+            "wal_bytes_per_sync",
+            rocksdb_options_get_wal_bytes_per_sync
+        ),
+        (
+            Db,
+            UInt,
+            "writable_file_max_buffer_size",
+            rocksdb_options_get_writable_file_max_buffer_size
+        ),
+        (
+            Db,
+            Bool,
+            "allow_concurrent_memtable_write",
+            rocksdb_options_get_allow_concurrent_memtable_write
+        ),
+        (
+            Db,
+            Bool,
+            "enable_write_thread_adaptive_yield",
+            rocksdb_options_get_enable_write_thread_adaptive_yield
+        ),
+        (
+            Db,
+            UInt,
+            "delete_obsolete_files_period_micros",
+            rocksdb_options_get_delete_obsolete_files_period_micros
+        ),
+        (
+            Db,
+            Bool,
+            "enable_pipelined_write",
+            rocksdb_options_get_enable_pipelined_write
+        ),
+        (
+            Db,
+            Bool,
+            "unordered_write",
+            rocksdb_options_get_unordered_write
+        ),
+        (
+            Db,
+            Bool,
+            "skip_stats_update_on_db_open",
+            rocksdb_options_get_skip_stats_update_on_db_open
+        ),
+        (
+            Db,
+            Bool,
+            "skip_checking_sst_file_sizes_on_db_open",
+            rocksdb_options_get_skip_checking_sst_file_sizes_on_db_open
+        ),
+        (
+            Db,
+            Bool,
+            "allow_ingest_behind",
+            rocksdb_options_get_allow_ingest_behind
+        ),
+        (
+            Db,
+            UInt,
+            "compaction_readahead_size",
+            rocksdb_options_get_compaction_readahead_size
+        ),
+        (
+            Db,
+            Bool,
+            "avoid_unnecessary_blocking_io",
+            rocksdb_options_get_avoid_unnecessary_blocking_io
+        ),
+        (
+            Db,
+            Float,
+            "experimental_mempurge_threshold",
+            rocksdb_options_get_experimental_mempurge_threshold
+        ),
+        (
+            Db,
+            wal_recovery_mode_name,
+            "wal_recovery_mode",
+            rocksdb_options_get_wal_recovery_mode
+        ),
+        (Db, Bool, "atomic_flush", rocksdb_options_get_atomic_flush),
+        (
+            Db,
+            Bool,
+            "manual_wal_flush",
+            rocksdb_options_get_manual_wal_flush
+        ),
+        (
+            Db,
+            compression_name,
+            "wal_compression",
+            rocksdb_options_get_wal_compression
+        ),
+        // column family options
+        (
+            Cf,
+            UInt,
+            "write_buffer_size",
+            rocksdb_options_get_write_buffer_size
+        ),
+        (
+            Cf,
+            Int,
+            "max_write_buffer_number",
+            rocksdb_options_get_max_write_buffer_number
+        ),
+        (
+            Cf,
+            Int,
+            "min_write_buffer_number_to_merge",
+            rocksdb_options_get_min_write_buffer_number_to_merge
+        ),
+        (
+            Cf,
+            Int,
+            "max_write_buffer_number_to_maintain",
+            rocksdb_options_get_max_write_buffer_number_to_maintain
+        ),
+        (
+            Cf,
+            Int,
+            "max_write_buffer_size_to_maintain",
+            rocksdb_options_get_max_write_buffer_size_to_maintain
+        ),
+        (
+            Cf,
+            compression_name,
+            "compression",
+            rocksdb_options_get_compression
+        ),
+        (
+            Cf,
+            compression_name,
+            "bottommost_compression",
+            rocksdb_options_get_bottommost_compression
+        ),
+        (
+            Cf,
+            compaction_style_name,
+            "compaction_style",
+            rocksdb_options_get_compaction_style
+        ),
+        (Cf, Int, "num_levels", rocksdb_options_get_num_levels),
+        (
+            Cf,
+            Int,
+            "level0_file_num_compaction_trigger",
+            rocksdb_options_get_level0_file_num_compaction_trigger
+        ),
+        (
+            Cf,
+            Int,
+            "level0_slowdown_writes_trigger",
+            rocksdb_options_get_level0_slowdown_writes_trigger
+        ),
+        (
+            Cf,
+            Int,
+            "level0_stop_writes_trigger",
+            rocksdb_options_get_level0_stop_writes_trigger
+        ),
+        (
+            Cf,
+            UInt,
+            "target_file_size_base",
+            rocksdb_options_get_target_file_size_base
+        ),
+        (
+            Cf,
+            Int,
+            "target_file_size_multiplier",
+            rocksdb_options_get_target_file_size_multiplier
+        ),
+        (
+            Cf,
+            UInt,
+            "max_bytes_for_level_base",
+            rocksdb_options_get_max_bytes_for_level_base
+        ),
+        (
+            Cf,
+            Bool,
+            "level_compaction_dynamic_level_bytes",
+            rocksdb_options_get_level_compaction_dynamic_level_bytes
+        ),
+        (
+            Cf,
+            Float,
+            "max_bytes_for_level_multiplier",
+            rocksdb_options_get_max_bytes_for_level_multiplier
+        ),
+        (
+            Cf,
+            UInt,
+            "max_compaction_bytes",
+            rocksdb_options_get_max_compaction_bytes
+        ),
+        (
+            Cf,
+            UInt,
+            "soft_pending_compaction_bytes_limit",
+            rocksdb_options_get_soft_pending_compaction_bytes_limit
+        ),
+        (
+            Cf,
+            UInt,
+            "hard_pending_compaction_bytes_limit",
+            rocksdb_options_get_hard_pending_compaction_bytes_limit
+        ),
+        (
+            Cf,
+            UInt,
+            "periodic_compaction_seconds",
+            rocksdb_options_get_periodic_compaction_seconds
+        ),
+        (
+            Cf,
+            Bool,
+            "disable_auto_compactions",
+            rocksdb_options_get_disable_auto_compactions
+        ),
+        (
+            Cf,
+            Bool,
+            "optimize_filters_for_hits",
+            rocksdb_options_get_optimize_filters_for_hits
+        ),
+        (
+            Cf,
+            UInt,
+            "arena_block_size",
+            rocksdb_options_get_arena_block_size
+        ),
+        (
+            Cf,
+            UInt,
+            "max_sequential_skip_in_iterations",
+            rocksdb_options_get_max_sequential_skip_in_iterations
+        ),
+        (
+            Cf,
+            Float,
+            "memtable_prefix_bloom_size_ratio",
+            rocksdb_options_get_memtable_prefix_bloom_size_ratio
+        ),
+        (
+            Cf,
+            UInt,
+            "memtable_huge_page_size",
+            rocksdb_options_get_memtable_huge_page_size
+        ),
+        (
+            Cf,
+            UInt,
+            "max_successive_merges",
+            rocksdb_options_get_max_successive_merges
+        ),
+        (
+            Cf,
+            UInt,
+            "bloom_locality",
+            rocksdb_options_get_bloom_locality
+        ),
+        (
+            Cf,
+            Bool,
+            "inplace_update_support",
+            rocksdb_options_get_inplace_update_support
+        ),
+        (
+            Cf,
+            UInt,
+            "inplace_update_num_locks",
+            rocksdb_options_get_inplace_update_num_locks
+        ),
+        (
+            Cf,
+            Bool,
+            "report_bg_io_stats",
+            rocksdb_options_get_report_bg_io_stats
+        ),
+        (
+            Cf,
+            Bool,
+            "enable_blob_files",
+            rocksdb_options_get_enable_blob_files
+        ),
+        (Cf, UInt, "min_blob_size", rocksdb_options_get_min_blob_size),
+        (
+            Cf,
+            UInt,
+            "blob_file_size",
+            rocksdb_options_get_blob_file_size
+        ),
+        (
+            Cf,
+            compression_name,
+            "blob_compression_type",
+            rocksdb_options_get_blob_compression_type
+        ),
+        (
+            Cf,
+            Bool,
+            "enable_blob_garbage_collection",
+            rocksdb_options_get_enable_blob_gc
+        ),
+        (
+            Cf,
+            Float,
+            "blob_garbage_collection_age_cutoff",
+            rocksdb_options_get_blob_gc_age_cutoff
+        ),
+        (
+            Cf,
+            Float,
+            "blob_garbage_collection_force_threshold",
+            rocksdb_options_get_blob_gc_force_threshold
+        ),
+        (
+            Cf,
+            UInt,
+            "blob_compaction_readahead_size",
+            rocksdb_options_get_blob_compaction_readahead_size
+        ),
+        (
+            Cf,
+            Int,
+            "blob_file_starting_level",
+            rocksdb_options_get_blob_file_starting_level
+        ),
+        (
+            Cf,
+            prepopulate_blob_cache_name,
+            "prepopulate_blob_cache",
+            rocksdb_options_get_prepopulate_blob_cache
+        ),
+    )
+}
+
+/// Column family options that have no getter in the C API,
+/// with the type of their values: only found in OPTIONS files.
+pub(crate) const PERSISTED_ONLY_CF_OPTIONS: [(&str, OptionValue); 3] = [
+    ("ttl", OptionValue::UInt(0)),
+    ("compaction_pri", OptionValue::Enum("")),
+    ("bottommost_compression_opts", OptionValue::Enum("")),
+];
+
+/// The options in the latest OPTIONS file of a DB, as written by RocksDB.
+pub(crate) struct PersistedOptions {
+    /// the `[DBOptions]` section
+    pub(crate) db: HashMap<String, String>,
+    /// the `[CFOptions "<name>"]` sections
+    pub(crate) cfs: HashMap<String, HashMap<String, String>>,
+}
+
+enum Section {
+    Db,
+    Cf(String),
+    Other,
+}
+
+impl PersistedOptions {
+    /// load the latest OPTIONS file in `path`, `None` if there is none
+    pub(crate) fn load_latest(path: &str) -> PyResult<Option<Self>> {
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(None),
+        };
+        let mut latest: Option<(u64, PathBuf)> = None;
+        for entry in entries {
+            let entry = entry?;
+            let number = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("OPTIONS-"))
+                .and_then(|number| number.parse::<u64>().ok());
+            if let Some(number) = number {
+                if latest.as_ref().map_or(true, |(n, _)| number > *n) {
+                    latest = Some((number, entry.path()));
+                }
+            }
+        }
+        match latest {
+            Some((_, file)) => Ok(Some(PersistedOptions::parse(&fs::read_to_string(file)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut db = HashMap::new();
+        let mut cfs: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut section = Section::Other;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = match header.strip_prefix("CFOptions ") {
+                    Some(name) => Section::Cf(name.trim().trim_matches('"').to_string()),
+                    None if header == "DBOptions" => Section::Db,
+                    None => Section::Other,
+                };
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                let (key, value) = (key.trim().to_string(), value.trim().to_string());
+                match &section {
+                    Section::Db => {
+                        db.insert(key, value);
+                    }
+                    Section::Cf(name) => {
+                        cfs.entry(name.clone()).or_default().insert(key, value);
+                    }
+                    Section::Other => {}
+                }
+            }
+        }
+        PersistedOptions { db, cfs }
+    }
+}
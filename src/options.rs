@@ -1,16 +1,23 @@
+use crate::codec::{CodecPy, ValueCompressionPy, ValueCompressionType};
 use crate::encoder::encode_key;
-use crate::rdict::{RocksDictConfig, ROCKSDICT_CONFIG_FILE};
+use crate::exceptions::{ffi_error, rocksdb_error, InvalidArgumentError};
+use crate::namespace::{compare_keys, root_upper_bound, KeyNamespace};
+use crate::option_values::{option_values, OptionScope, PersistedOptions};
+use crate::rdict::{
+    comparator_name, RocksDictConfig, NAMESPACED_ROCKSDICT_COMPARATOR, ROCKSDICT_COMPARATOR,
+    ROCKSDICT_CONFIG_FILE,
+};
 use libc::{c_char, c_uchar, size_t};
-use num_bigint::BigInt;
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList, PyTuple};
+use pyo3::types::{PyBool, PyDict, PyList, PyTuple};
 use rocksdb::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::ffi::c_double;
+use std::ffi::{c_double, CString};
 use std::os::raw::{c_int, c_uint};
 use std::path::{Path, PathBuf};
+use std::ptr::null_mut;
 
 /// Database-wide options around performance and behavior.
 ///
@@ -55,6 +62,13 @@ pub(crate) struct OptionsPy {
     pub(crate) inner_opt: Options,
     pub(crate) raw_mode: bool,
     pub(crate) prefix_extractor: Option<SliceTransformType>,
+    pub(crate) codec: Option<CodecPy>,
+    pub(crate) value_compression: Option<ValueCompressionType>,
+    // name of the table factory set on these options, `None` if unknown
+    // (options loaded from OPTIONS files)
+    pub(crate) table_factory: Option<&'static str>,
+    // whether the comparator orders namespaced keys (non-raw mode only)
+    pub(crate) namespaces: bool,
 }
 
 /// Optionally disable WAL or sync for this write.
@@ -201,8 +215,12 @@ pub(crate) struct ReadOpt(pub(crate) *mut librocksdb_sys::rocksdb_readoptions_t)
 pub(crate) struct MemtableFactoryPy(MemtableFactory);
 
 /// For configuring block-based file storage.
+///
+/// The second field holds the table options that the rocksdb crate has no
+/// setter for, as `name=value` pairs: they are applied with an option string
+/// when the table factory is set on `Options`.
 #[pyclass(name = "BlockBasedOptions")]
-pub(crate) struct BlockBasedOptionsPy(BlockBasedOptions);
+pub(crate) struct BlockBasedOptionsPy(BlockBasedOptions, Vec<String>);
 
 /// Configuration of cuckoo-based storage.
 #[pyclass(name = "CuckooTableOptions")]
@@ -266,9 +284,43 @@ pub(crate) struct PlainTableFactoryOptionsPy {
     store_index_in_file: bool,
 }
 
+/// A cache and its capacity, which cannot be read back from `rocksdb::Cache`.
 #[pyclass(name = "Cache")]
 #[derive(Clone)]
-pub(crate) struct CachePy(Cache);
+pub(crate) struct CachePy(Cache, usize);
+
+/// Limits the total memory of the memtables of several column families
+/// and/or databases: pass the same instance to `Options.set_write_buffer_manager`
+/// of each of them.
+///
+/// When the mutable memtables use about 90% of `buffer_size`, a flush is
+/// triggered on the database being written to. More aggressive flushes are
+/// triggered when the total memory exceeds `buffer_size`.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, Options, WriteBufferManager, Cache
+///
+///         # 256MB for all the memtables, charged to a 1GB block cache
+///         cache = Cache(1024 * 1024 * 1024)
+///         manager = WriteBufferManager(256 * 1024 * 1024, cache=cache)
+///         opts = Options()
+///         opts.set_write_buffer_manager(manager)
+///
+///         dbs = [Rdict(f"tmp_{i}", opts) for i in range(10)]
+///         print(manager.usage(), manager.buffer_size())
+///
+/// Args:
+///     buffer_size: the memory limit of the memtables, in bytes
+///         (0 to only charge the memtables to `cache`).
+///     allow_stall: if True, writes stall when the memory usage exceeds
+///         `buffer_size`, until flushes bring it down.
+///     cache: if set, the memory of the memtables is also charged to this
+///         (block) cache, to cap the memtables and the cache with one limit.
+#[pyclass(name = "WriteBufferManager")]
+#[derive(Clone)]
+pub(crate) struct WriteBufferManagerPy(WriteBufferManager);
 
 #[pyclass(name = "BlockBasedIndexType")]
 pub(crate) struct BlockBasedIndexTypePy(BlockBasedIndexType);
@@ -286,6 +338,16 @@ pub enum SliceTransformType {
     Noop,
 }
 
+impl std::fmt::Display for SliceTransformType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceTransformType::Fixed(len) => write!(f, "fixed_prefix({len})"),
+            SliceTransformType::MaxLen(len) => write!(f, "max_len_prefix({len})"),
+            SliceTransformType::Noop => write!(f, "noop"),
+        }
+    }
+}
+
 #[pyclass(name = "DBPath")]
 pub(crate) struct DBPathPy {
     path: PathBuf,
@@ -334,6 +396,48 @@ pub(crate) struct DBCompressionTypePy(DBCompressionType);
 #[pyclass(name = "DBCompactionStyle")]
 pub(crate) struct DBCompactionStylePy(DBCompactionStyle);
 
+/// This is to be treated as an enum.
+///
+/// Call the corresponding functions of each
+/// to get one of the following.
+/// - ByCompensatedSize
+/// - OldestLargestSeqFirst
+/// - OldestSmallestSeqFirst
+/// - MinOverlappingRatio
+/// - RoundRobin
+///
+/// Below is an example to set compaction priority to MinOverlappingRatio.
+///
+/// Example:
+///     ::
+///
+///         opt = Options()
+///         opt.set_compaction_pri(DBCompactionPri.min_overlapping_ratio())
+///
+#[pyclass(name = "DBCompactionPri")]
+pub(crate) struct DBCompactionPriPy(&'static str);
+
+/// This is to be treated as an enum.
+///
+/// Call the corresponding functions of each
+/// to get one of the following.
+/// - Fallback: use the default pinning of the block type
+/// - None: no pinning
+/// - FlushedAndSimilar: pin the blocks of files that are flushed,
+///   or about as small as flushed files
+/// - All: pin all blocks
+///
+/// Below is an example to pin the top-level index of all files.
+///
+/// Example:
+///     ::
+///
+///         block_opts = BlockBasedOptions()
+///         block_opts.set_top_level_index_pinning_tier(PinningTier.all())
+///
+#[pyclass(name = "PinningTier")]
+pub(crate) struct PinningTierPy(&'static str);
+
 /// Used by BlockBasedOptions::set_checksum_type.
 ///
 /// Call the corresponding functions of each
@@ -512,15 +616,19 @@ impl OptionsPy {
         let rocksdict_config = RocksDictConfig::load(config_path)?;
         let raw_mode = rocksdict_config.raw_mode;
         let slice_transforms = rocksdict_config.prefix_extractors;
+        let codecs = rocksdict_config.codecs;
+        let value_compressions = rocksdict_config.value_compressions;
         let load_result = Options::load_latest(path, env.0, ignore_unknown_options, cache.0);
         let (options, column_families) = match load_result {
             Ok(d) => d,
-            Err(e) => return Err(PyException::new_err(e.to_string())),
+            Err(e) => return Err(rocksdb_error(e)),
         };
         let options = OptionsPy::compose_options_py(
             options,
             raw_mode,
             slice_transforms.get(DEFAULT_COLUMN_FAMILY_NAME).cloned(),
+            codecs.get(DEFAULT_COLUMN_FAMILY_NAME),
+            value_compressions.get(DEFAULT_COLUMN_FAMILY_NAME).cloned(),
         )?;
         let column_families: PyResult<HashMap<_, _>> = column_families
             .into_iter()
@@ -529,6 +637,8 @@ impl OptionsPy {
                     c.options,
                     raw_mode,
                     slice_transforms.get(&c.name).cloned(),
+                    codecs.get(&c.name),
+                    value_compressions.get(&c.name).cloned(),
                 );
                 match opt {
                     Ok(opt) => Ok((c.name, opt)),
@@ -539,11 +649,14 @@ impl OptionsPy {
         Ok((options, column_families?))
     }
 
-    /// convert `Options` into `OptionsPy` based on `raw_mode` and `prefix_extractor`
-    fn compose_options_py(
+    /// convert `Options` into `OptionsPy` based on `raw_mode`, `prefix_extractor`,
+    /// `codec` and `value_compression`
+    pub(crate) fn compose_options_py(
         opt: Options,
         raw_mode: bool,
         prefix_extractor: Option<SliceTransformType>,
+        codec: Option<&String>,
+        value_compression: Option<ValueCompressionType>,
     ) -> PyResult<OptionsPy> {
         let mut opt = opt;
         if !raw_mode {
@@ -552,26 +665,225 @@ impl OptionsPy {
         if let Some(slice_transform) = &prefix_extractor {
             OptionsPy::set_prefix_extractor_inner(&mut opt, slice_transform)?
         }
+        let codec = match codec {
+            Some(name) => Some(Python::with_gil(|py| CodecPy::from_name(name, py))?),
+            None => None,
+        };
         let options = OptionsPy {
             inner_opt: opt,
             raw_mode,
             prefix_extractor,
+            codec,
+            value_compression,
+            table_factory: None,
+            namespaces: false,
         };
         Ok(options)
     }
 
+    /// the codec (with value compression) defined by these options, if any
+    pub(crate) fn value_codec(&self, py: Python) -> PyResult<Option<CodecPy>> {
+        if self.codec.is_none() && self.value_compression.is_none() {
+            return Ok(None);
+        }
+        let mut codec = match &self.codec {
+            Some(codec) => codec.clone(),
+            None => CodecPy::pickle(py)?,
+        };
+        codec.compression = self.value_compression.clone();
+        Ok(Some(codec))
+    }
+
     fn set_rocksdict_comparator(opt: &mut Options) {
-        opt.set_comparator(
-            "rocksdict",
-            Box::new(|v1, v2| {
-                if let (Some(3), Some(3)) = (v1.first(), v2.first()) {
-                    BigInt::from_signed_bytes_be(&v1[1..])
-                        .cmp(&BigInt::from_signed_bytes_be(&v2[1..]))
-                } else {
-                    v1.cmp(v2)
+        opt.set_comparator(ROCKSDICT_COMPARATOR, Box::new(compare_keys));
+    }
+
+    /// name of the comparator of these options
+    pub(crate) fn comparator_name(&self) -> &'static str {
+        comparator_name(self.raw_mode, self.namespaces)
+    }
+
+    /// set options that have no setter in the C API from a RocksDB option string
+    fn set_from_options_string(&mut self, options: &str) -> PyResult<()> {
+        let options = match CString::new(options) {
+            Ok(options) => options,
+            Err(_) => return Err(PyException::new_err("options contain null bytes")),
+        };
+        let mut err: *mut c_char = null_mut();
+        // the options are copied before being updated,
+        // so they can be both the base and the result
+        unsafe {
+            librocksdb_sys::rocksdb_get_options_from_string(
+                self.inner_opt.inner(),
+                options.as_ptr(),
+                self.inner_opt.inner(),
+                &mut err,
+            );
+        }
+        if !err.is_null() {
+            return Err(ffi_error(err));
+        }
+        Ok(())
+    }
+
+    /// Differences between these options, used for column family `cf_name`,
+    /// and the options persisted for it in the OPTIONS file and the rocksdict config.
+    ///
+    /// DB options are only compared for the default column family.
+    /// Returns `None` if the column family was not persisted.
+    fn persisted_diff(
+        &self,
+        cf_name: &str,
+        persisted: &PersistedOptions,
+        config: Option<&RocksDictConfig>,
+        py: Python,
+    ) -> Option<OptionsDiff> {
+        let persisted_cf = persisted.cfs.get(cf_name)?;
+        let mut diff = OptionsDiff::default();
+        if let Some(config) = config {
+            if config.raw_mode != self.raw_mode {
+                diff.incompatible.push((
+                    "raw_mode",
+                    Some(config.raw_mode.to_string()),
+                    Some(self.raw_mode.to_string()),
+                ));
+            }
+            let persisted_prefix = config.prefix_extractors.get(cf_name).map(|t| t.to_string());
+            let requested_prefix = self.prefix_extractor.as_ref().map(|t| t.to_string());
+            if persisted_prefix != requested_prefix {
+                diff.incompatible
+                    .push(("prefix_extractor", persisted_prefix, requested_prefix));
+            }
+        }
+        // namespaces are enabled when opening DBs created with them
+        let persisted_comparator = persisted_cf.get("comparator").map(|c| c.as_str());
+        let namespaced =
+            !self.raw_mode && persisted_comparator == Some(NAMESPACED_ROCKSDICT_COMPARATOR);
+        if persisted_comparator != Some(self.comparator_name()) && !namespaced {
+            diff.incompatible.push((
+                "comparator",
+                persisted_comparator.map(|c| c.to_string()),
+                Some(self.comparator_name().to_string()),
+            ));
+        }
+        if let Some(table_factory) = self.table_factory {
+            let persisted_table_factory = persisted_cf.get("table_factory");
+            if persisted_table_factory.map(|t| t.as_str()) != Some(table_factory) {
+                diff.incompatible.push((
+                    "table_factory",
+                    persisted_table_factory.cloned(),
+                    Some(table_factory.to_string()),
+                ));
+            }
+        }
+        for (name, scope, value) in option_values(&self.inner_opt) {
+            let persisted_value = match scope {
+                OptionScope::Db if cf_name == DEFAULT_COLUMN_FAMILY_NAME => persisted.db.get(name),
+                OptionScope::Db => None,
+                OptionScope::Cf => persisted_cf.get(name),
+            };
+            if let Some(persisted_value) = persisted_value {
+                if !value.matches(persisted_value) {
+                    diff.changed.push((
+                        name,
+                        value.persisted_object(persisted_value, py),
+                        value.to_object(py),
+                    ));
+                }
+            }
+        }
+        Some(diff)
+    }
+
+    /// Compare `options` (for the default column family) and `column_families`
+    /// with the options persisted in the DB at `path`, if any.
+    pub(crate) fn compatibility_diff(
+        path: &str,
+        options: Option<&OptionsPy>,
+        column_families: Option<&HashMap<String, OptionsPy>>,
+        py: Python,
+    ) -> PyResult<Vec<(String, OptionsDiff)>> {
+        let persisted = match PersistedOptions::load_latest(path)? {
+            Some(persisted) => persisted,
+            None => return Ok(Vec::new()),
+        };
+        let mut config_path = PathBuf::from(path);
+        config_path.push(ROCKSDICT_CONFIG_FILE);
+        let config = RocksDictConfig::load(config_path).ok();
+        let mut requested: Vec<(&str, &OptionsPy)> = Vec::new();
+        if let Some(options) = options {
+            requested.push((DEFAULT_COLUMN_FAMILY_NAME, options));
+        }
+        if let Some(column_families) = column_families {
+            for (name, opt) in column_families {
+                if options.is_none() || name != DEFAULT_COLUMN_FAMILY_NAME {
+                    requested.push((name.as_str(), opt));
                 }
-            }),
-        );
+            }
+        }
+        Ok(requested
+            .into_iter()
+            .filter_map(|(name, opt)| {
+                opt.persisted_diff(name, &persisted, config.as_ref(), py)
+                    .map(|diff| (name.to_string(), diff))
+            })
+            .collect())
+    }
+
+    /// Raise `InvalidArgumentError` if `options` or `column_families`
+    /// are incompatible with the options persisted in the DB at `path`.
+    pub(crate) fn verify_compatibility(
+        path: &str,
+        options: Option<&OptionsPy>,
+        column_families: Option<&HashMap<String, OptionsPy>>,
+        py: Python,
+    ) -> PyResult<()> {
+        let mut errors = Vec::new();
+        for (cf_name, diff) in OptionsPy::compatibility_diff(path, options, column_families, py)? {
+            for (name, persisted, requested) in diff.incompatible {
+                errors.push(format!(
+                    "{name} of column family '{cf_name}' is {}, but {} is requested",
+                    persisted.as_deref().unwrap_or("not set"),
+                    requested.as_deref().unwrap_or("not set"),
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidArgumentError::new_err(format!(
+                "options incompatible with the persisted options: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+}
+
+/// Differences between requested options and persisted options,
+/// as `(option name, persisted value, requested value)`.
+#[derive(Default)]
+pub(crate) struct OptionsDiff {
+    /// settings that the DB cannot be opened with (comparator, `raw_mode`,
+    /// prefix extractor and table factory)
+    pub(crate) incompatible: Vec<(&'static str, Option<String>, Option<String>)>,
+    /// option values that are changed
+    pub(crate) changed: Vec<(&'static str, PyObject, PyObject)>,
+}
+
+impl OptionsDiff {
+    fn to_object(&self, py: Python) -> PyResult<PyObject> {
+        let incompatible = PyDict::new(py);
+        for (name, persisted, requested) in &self.incompatible {
+            incompatible.set_item(name, (persisted, requested))?;
+        }
+        let changed = PyDict::new(py);
+        for (name, persisted, requested) in &self.changed {
+            changed.set_item(name, (persisted, requested))?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("incompatible", incompatible)?;
+        result.set_item("changed", changed)?;
+        Ok(result.to_object(py))
     }
 }
 
@@ -590,6 +902,10 @@ impl OptionsPy {
             inner_opt: opt,
             raw_mode,
             prefix_extractor: None,
+            codec: None,
+            value_compression: None,
+            table_factory: Some("BlockBasedTable"),
+            namespaces: false,
         }
     }
 
@@ -622,6 +938,164 @@ impl OptionsPy {
         Ok(returned_tuple.to_object(py))
     }
 
+    /// Returns the DB options and column family options as a `dict`,
+    /// with option names as used in RocksDB option strings and OPTIONS files.
+    ///
+    /// Pointer options (table factory, caches, comparator, prefix extractor,
+    /// merge operator, ...) cannot be read back and are not included.
+    /// Neither are the options that the RocksDB C API has no getter for:
+    /// `ttl`, `compaction_pri` and `bottommost_compression_opts` (see
+    /// `Rdict.get_options`, which reads them from the OPTIONS file).
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options
+    ///
+    ///         opts = Options()
+    ///         opts.set_write_buffer_size(128 * 1024 * 1024)
+    ///         assert opts.to_dict()["write_buffer_size"] == 128 * 1024 * 1024
+    pub fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (name, _, value) in option_values(&self.inner_opt) {
+            dict.set_item(name, value.to_object(py))?;
+        }
+        Ok(dict.to_object(py))
+    }
+
+    /// Returns the options in the format of RocksDB option strings
+    /// (`"name1=value1;name2=value2"`), which can be read back
+    /// by `Options.from_options_string`.
+    ///
+    /// The same options as in `Options.to_dict()` are included.
+    pub fn to_options_string(&self) -> String {
+        option_values(&self.inner_opt)
+            .iter()
+            .map(|(name, _, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// Create `Options` from a `dict` of option names and values
+    /// (see `Options.to_dict()`).
+    ///
+    /// Any option accepted by RocksDB option strings can be set,
+    /// with values given as `bool`, `int`, `float` or `str`.
+    ///
+    /// Args:
+    ///     options: dict of option names and values.
+    ///     raw_mode: the `raw_mode` of the created `Options`.
+    ///
+    /// Raises:
+    ///     InvalidArgumentError: for unknown options or invalid values.
+    #[staticmethod]
+    #[pyo3(signature = (options, raw_mode = false))]
+    pub fn from_dict(options: &PyDict, raw_mode: bool) -> PyResult<Self> {
+        let mut items = Vec::with_capacity(options.len());
+        for (name, value) in options.iter() {
+            let name: &str = name.extract()?;
+            let value = if let Ok(b) = value.downcast::<PyBool>() {
+                b.is_true().to_string()
+            } else {
+                value.str()?.to_str()?.to_string()
+            };
+            items.push(format!("{name}={value}"));
+        }
+        OptionsPy::from_options_string(&items.join(";"), raw_mode)
+    }
+
+    /// Create `Options` from a RocksDB option string, such as
+    /// `"write_buffer_size=1048576;max_write_buffer_number=4"`.
+    ///
+    /// Both DB options and column family options are accepted,
+    /// using the names of RocksDB OPTIONS files.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options
+    ///
+    ///         opts = Options.from_options_string(
+    ///             "write_buffer_size=1048576;compression=kZSTD"
+    ///         )
+    ///
+    /// Args:
+    ///     options: the option string.
+    ///     raw_mode: the `raw_mode` of the created `Options`.
+    ///
+    /// Raises:
+    ///     InvalidArgumentError: for unknown options or invalid values.
+    #[staticmethod]
+    #[pyo3(signature = (options, raw_mode = false))]
+    pub fn from_options_string(options: &str, raw_mode: bool) -> PyResult<Self> {
+        let options = match CString::new(options) {
+            Ok(options) => options,
+            Err(_) => return Err(PyException::new_err("options contain null bytes")),
+        };
+        let mut base = Options::default();
+        base.create_if_missing(true);
+        let opt = Options::default();
+        let mut err: *mut c_char = null_mut();
+        unsafe {
+            librocksdb_sys::rocksdb_get_options_from_string(
+                base.inner(),
+                options.as_ptr(),
+                opt.inner(),
+                &mut err,
+            );
+        }
+        if !err.is_null() {
+            return Err(ffi_error(err));
+        }
+        OptionsPy::compose_options_py(opt, raw_mode, None, None, None)
+    }
+
+    /// Compare these options (and those of `column_families`) with the options
+    /// persisted in the latest OPTIONS file and the rocksdict config of the DB
+    /// at `path`, without opening it.
+    ///
+    /// Settings the DB cannot be reopened with (comparator, `raw_mode`,
+    /// prefix extractor and table factory) are reported as `incompatible`,
+    /// other option values that differ as `changed`.
+    /// DB options are only compared for the default column family.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options
+    ///
+    ///         opts = Options(raw_mode=True)
+    ///         diff = opts.check_compatibility("./db_path")
+    ///         # {"default": {"incompatible": {"raw_mode": ("false", "true"), ...},
+    ///         #              "changed": {"write_buffer_size": (67108864, 1048576)}}}
+    ///         if diff["default"]["incompatible"]:
+    ///             ...
+    ///
+    /// Args:
+    ///     path: path of the DB.
+    ///     column_families: (name, options) pairs of column families to compare.
+    ///
+    /// Returns:
+    ///     a dict mapping the names of the persisted column families compared
+    ///     to a dict with the `incompatible` settings and `changed` options,
+    ///     both mapping option names to `(persisted, requested)` values.
+    ///     Empty if the DB has no OPTIONS file.
+    #[pyo3(signature = (path, column_families = None))]
+    pub fn check_compatibility(
+        &self,
+        path: &str,
+        column_families: Option<HashMap<String, OptionsPy>>,
+        py: Python,
+    ) -> PyResult<PyObject> {
+        let result = PyDict::new(py);
+        for (cf_name, diff) in
+            OptionsPy::compatibility_diff(path, Some(self), column_families.as_ref(), py)?
+        {
+            result.set_item(cf_name, diff.to_object(py)?)?;
+        }
+        Ok(result.to_object(py))
+    }
+
     /// By default, RocksDB uses only one background thread for flush and
     /// compaction. Calling this function will set it up such that total of
     /// `total_threads` is used. Good value for `total_threads` is the number of
@@ -853,6 +1327,62 @@ impl OptionsPy {
         self.inner_opt.set_zstd_max_train_bytes(value)
     }
 
+    /// Sets the compression algorithm used for the bottommost level
+    /// (where most of the data lives), overriding `set_compression_type`
+    /// and `set_compression_per_level` there.
+    ///
+    /// Combined with `set_periodic_compaction_seconds`, old data gets
+    /// recompacted into the bottommost level with a stronger compression.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options, DBCompressionType
+    ///
+    ///         opts = Options()
+    ///         opts.set_compression_type(DBCompressionType.lz4())
+    ///         opts.set_bottommost_compression_type(DBCompressionType.zstd())
+    ///         opts.set_bottommost_compression_options(-14, 19, 0, 0, True)
+    ///
+    /// Default: `DBCompressionType.disable()` (the same as the other levels)
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_bottommost_compression_type(&mut self, t: &DBCompressionTypePy) {
+        self.inner_opt.set_bottommost_compression_type(t.0)
+    }
+
+    /// Sets the compression options of the bottommost level,
+    /// see `set_compression_options`.
+    ///
+    /// The options are only used if `enabled` is `True`,
+    /// otherwise those of `set_compression_options` are used.
+    pub fn set_bottommost_compression_options(
+        &mut self,
+        w_bits: c_int,
+        level: c_int,
+        strategy: c_int,
+        max_dict_bytes: c_int,
+        enabled: bool,
+    ) {
+        self.inner_opt.set_bottommost_compression_options(
+            w_bits,
+            level,
+            strategy,
+            max_dict_bytes,
+            enabled,
+        )
+    }
+
+    /// Sets maximum size of training data passed to zstd's dictionary trainer
+    /// for the bottommost level, see `set_zstd_max_train_bytes`.
+    ///
+    /// `enabled` enables the bottommost compression options
+    /// (see `set_bottommost_compression_options`).
+    pub fn set_bottommost_zstd_max_train_bytes(&mut self, value: c_int, enabled: bool) {
+        self.inner_opt
+            .set_bottommost_zstd_max_train_bytes(value, enabled)
+    }
+
     /// If non-zero, we perform bigger reads when doing compaction. If you're
     /// running RocksDB on spinning disks, you should set this to at least 2MB.
     /// That way RocksDB's compaction is doing sequential instead of random reads.
@@ -899,6 +1429,24 @@ impl OptionsPy {
     //     self.inner_opt.set_comparator(name, compare_fn)
     // }
 
+    /// Allow `Rdict.namespace` on the DB, default false.
+    ///
+    /// Except in raw mode, namespaced keys need the "rocksdict.v2" comparator,
+    /// which rocksdict versions without namespaces refuse to open, instead of
+    /// the "rocksdict" comparator. It must be enabled when the DB is created,
+    /// and is enabled automatically when a DB created with it is reopened.
+    /// In raw mode, namespaces are always allowed and this has no effect.
+    ///
+    /// Args:
+    ///     enabled (bool): whether namespaces are allowed.
+    pub fn set_enable_namespaces(&mut self, enabled: bool) {
+        if !self.raw_mode {
+            self.inner_opt
+                .set_comparator(comparator_name(false, enabled), Box::new(compare_keys));
+            self.namespaces = enabled;
+        }
+    }
+
     pub fn set_prefix_extractor(&mut self, prefix_extractor: &SliceTransformPy) -> PyResult<()> {
         let transform = match &prefix_extractor.0 {
             SliceTransformType::Fixed(len) => SliceTransform::create_fixed_prefix(*len),
@@ -917,6 +1465,34 @@ impl OptionsPy {
         Ok(())
     }
 
+    /// Set the codec used to serialize values that are not natively
+    /// supported (see `Codec`). The codec name is persisted in the
+    /// rocksdict config file of the database.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options, Codec
+    ///
+    ///         opt = Options()
+    ///         opt.set_codec(Codec.json())
+    ///         db = Rdict("./json_db", opt)
+    ///         db["key"] = {"a": [1, 2.5, None]}
+    pub fn set_codec(&mut self, codec: CodecPy) {
+        self.codec = Some(codec);
+    }
+
+    /// Compress values larger than a threshold before writing them
+    /// (see `ValueCompression`). The setting is persisted in the
+    /// rocksdict config file of the database.
+    ///
+    /// Args:
+    ///     compression (ValueCompression | None): `None` disables value compression.
+    #[pyo3(signature = (compression))]
+    pub fn set_value_compression(&mut self, compression: Option<ValueCompressionPy>) {
+        self.value_compression = compression.map(|c| c.0);
+    }
+
     // pub fn add_comparator(&mut self, name: &str, compare_fn: CompareFn) {
     //     self.0.add_comparator(name, compare_fn)
     // }
@@ -1271,6 +1847,21 @@ impl OptionsPy {
         self.inner_opt.set_compaction_style(style.0)
     }
 
+    /// Sets the priority of the files picked for level compaction.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options, DBCompactionPri
+    ///
+    ///         opts = Options()
+    ///         opts.set_compaction_pri(DBCompactionPri.min_overlapping_ratio())
+    ///
+    /// Default: DBCompactionPri.min_overlapping_ratio()
+    pub fn set_compaction_pri(&mut self, pri: &DBCompactionPriPy) -> PyResult<()> {
+        self.set_from_options_string(&format!("compaction_pri={}", pri.0))
+    }
+
     /// Sets the options needed to support Universal Style compactions.
     pub fn set_universal_compaction_options(&mut self, uco: &UniversalCompactOptionsPy) {
         self.inner_opt.set_universal_compaction_options(&uco.into())
@@ -1281,6 +1872,56 @@ impl OptionsPy {
         self.inner_opt.set_fifo_compaction_options(&fco.into())
     }
 
+    /// Files older than this value (in seconds) are picked up for
+    /// compaction, and rewritten to the same level (or to the bottommost
+    /// level with its compression, see `set_bottommost_compression_type`).
+    /// Useful to make sure that old data gets recompacted.
+    ///
+    /// `0` disables it.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options
+    ///
+    ///         opts = Options()
+    ///         opts.set_periodic_compaction_seconds(7 * 24 * 3600)
+    ///
+    /// Default: 30 days with a compaction filter, disabled otherwise
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_periodic_compaction_seconds(&mut self, secs: u64) {
+        self.inner_opt.set_periodic_compaction_seconds(secs)
+    }
+
+    /// Sets the `ttl` (in seconds) used by compactions of the column family
+    /// opened with these options.
+    ///
+    /// With FIFO compaction, SST files older than `ttl` are deleted.
+    /// With level compaction, non-bottommost files older than `ttl` are
+    /// compacted, so that deletions and overwrites get dropped.
+    /// Unlike `AccessType.with_ttl` and the `(Options, ttl_seconds)`
+    /// column families of `Rdict`, entries do not expire individually:
+    /// expiry is per file, based on the age of its oldest entry.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options, DBCompactionStyle
+    ///
+    ///         cache_opt = Options()
+    ///         cache_opt.set_compaction_style(DBCompactionStyle.fifo())
+    ///         cache_opt.set_ttl(24 * 3600)
+    ///         db = Rdict("./ttl_db", column_families={"cache": cache_opt})
+    ///
+    /// Notes:
+    ///     `0` disables it.
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_ttl(&mut self, secs: u64) -> PyResult<()> {
+        self.set_from_options_string(&format!("ttl={secs}"))
+    }
+
     /// Sets unordered_write to true trades higher write throughput with
     /// relaxing the immutability guarantee of snapshots. This violates the
     /// repeatability one expects from ::Get from a snapshot, as well as
@@ -1509,8 +2150,17 @@ impl OptionsPy {
         })
     }
 
-    pub fn set_block_based_table_factory(&mut self, factory: &BlockBasedOptionsPy) {
-        self.inner_opt.set_block_based_table_factory(&factory.0)
+    pub fn set_block_based_table_factory(&mut self, factory: &BlockBasedOptionsPy) -> PyResult<()> {
+        self.inner_opt.set_block_based_table_factory(&factory.0);
+        self.table_factory = Some("BlockBasedTable");
+        // the factory options are copied, and then updated
+        if !factory.1.is_empty() {
+            self.set_from_options_string(&format!(
+                "block_based_table_factory={{{}}}",
+                factory.1.join(";")
+            ))?;
+        }
+        Ok(())
     }
 
     /// Sets the table factory to a CuckooTableFactory (the default table
@@ -1533,7 +2183,8 @@ impl OptionsPy {
     ///
     ///         opts.set_cuckoo_table_factory(factory_opts)
     pub fn set_cuckoo_table_factory(&mut self, factory: &CuckooTableOptionsPy) {
-        self.inner_opt.set_cuckoo_table_factory(&factory.0)
+        self.inner_opt.set_cuckoo_table_factory(&factory.0);
+        self.table_factory = Some("CuckooTable");
     }
 
     /// This is a factory that provides TableFactory objects.
@@ -1559,7 +2210,8 @@ impl OptionsPy {
     ///         opts.set_plain_table_factory(factory_opts)
     pub fn set_plain_table_factory(&mut self, options: &PlainTableFactoryOptionsPy) {
         self.inner_opt
-            .set_plain_table_factory(&options.to_opt(self.raw_mode))
+            .set_plain_table_factory(&options.to_opt(self.raw_mode));
+        self.table_factory = Some("PlainTable");
     }
 
     /// Sets the start level to use compression.
@@ -1758,6 +2410,152 @@ impl OptionsPy {
         self.inner_opt.set_row_cache(&cache.0)
     }
 
+    /// Adds a table properties collector which marks an SST file as
+    /// needing compaction when it observes at least `num_dels_trigger`
+    /// deletions in any `window_size` consecutive entries, or when
+    /// the ratio of deletions in the file is at least `deletion_ratio`.
+    ///
+    /// This is RocksDB's built-in `CompactOnDeletionCollector`, useful to
+    /// reclaim the space of deleted keys (e.g. in queue-like workloads)
+    /// sooner than regular compactions would.
+    ///
+    /// Args:
+    ///     window_size: the size of the sliding window of entries.
+    ///     num_dels_trigger: the number of deletions in the window
+    ///         triggering compaction.
+    ///     deletion_ratio: the ratio of deletions in the file triggering
+    ///         compaction, disabled if `<= 0` or `> 1`.
+    #[pyo3(signature = (window_size, num_dels_trigger, deletion_ratio = 0.0))]
+    pub fn add_compact_on_deletion_collector_factory(
+        &mut self,
+        window_size: usize,
+        num_dels_trigger: usize,
+        deletion_ratio: f64,
+    ) {
+        self.inner_opt.add_compact_on_deletion_collector_factory(
+            window_size,
+            num_dels_trigger,
+            deletion_ratio,
+        )
+    }
+
+    /// Enables integrated BlobDB: values of at least `min_blob_size` bytes
+    /// are written to separate blob files during flush and compaction,
+    /// so that compactions do not rewrite them (less write amplification
+    /// for large values).
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options, DBCompressionType
+    ///
+    ///         opts = Options()
+    ///         opts.set_enable_blob_files(True)
+    ///         opts.set_min_blob_size(4096)
+    ///         opts.set_blob_compression_type(DBCompressionType.lz4())
+    ///         opts.set_enable_blob_gc(True)
+    ///
+    /// Default: false
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_enable_blob_files(&mut self, val: bool) {
+        self.inner_opt.set_enable_blob_files(val)
+    }
+
+    /// Sets the minimum size of the values written to blob files
+    /// during flush or compaction.
+    ///
+    /// Default: 0
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_min_blob_size(&mut self, val: u64) {
+        self.inner_opt.set_min_blob_size(val)
+    }
+
+    /// Sets the size limit for blob files.
+    ///
+    /// Default: 256MB
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_file_size(&mut self, val: u64) {
+        self.inner_opt.set_blob_file_size(val)
+    }
+
+    /// Sets the compression type of the blob files.
+    /// All blob files use the same compression type.
+    ///
+    /// Default: no compression
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_compression_type(&mut self, t: &DBCompressionTypePy) {
+        self.inner_opt.set_blob_compression_type(t.0)
+    }
+
+    /// If true, compactions relocate the valid blobs of the oldest
+    /// blob files, so that these files can be deleted.
+    ///
+    /// Default: false
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_enable_blob_gc(&mut self, val: bool) {
+        self.inner_opt.set_enable_blob_gc(val)
+    }
+
+    /// Sets the part of the blob files considered old by garbage collection:
+    /// with the default 0.25, the blobs of the oldest 25% of blob files are
+    /// relocated. Tunes the trade-off between write and space amplification.
+    ///
+    /// Default: 0.25
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_gc_age_cutoff(&mut self, val: c_double) {
+        self.inner_opt.set_blob_gc_age_cutoff(val)
+    }
+
+    /// If the ratio of garbage in the old blob files is at least this
+    /// threshold, compactions of the SST files referencing them are
+    /// forced to clean them up.
+    ///
+    /// Default: 1.0
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_gc_force_threshold(&mut self, val: c_double) {
+        self.inner_opt.set_blob_gc_force_threshold(val)
+    }
+
+    /// Sets the read ahead size of compactions reading blob files.
+    ///
+    /// Default: 0
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_compaction_readahead_size(&mut self, val: u64) {
+        self.inner_opt.set_blob_compaction_readahead_size(val)
+    }
+
+    /// Sets the first LSM level from which values are written to blob
+    /// files: short-lived values in the first levels can stay inline.
+    ///
+    /// Default: 0
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn set_blob_file_starting_level(&mut self, level: c_int) {
+        unsafe {
+            librocksdb_sys::rocksdb_options_set_blob_file_starting_level(
+                self.inner_opt.inner(),
+                level,
+            )
+        }
+    }
+
+    /// Sets a `WriteBufferManager`, to limit the memory of the memtables
+    /// of several column families and/or databases with one budget.
+    ///
+    /// Default: null (each column family limited by its own `write_buffer_size`)
+    pub fn set_write_buffer_manager(&mut self, write_buffer_manager: &WriteBufferManagerPy) {
+        self.inner_opt
+            .set_write_buffer_manager(&write_buffer_manager.0)
+    }
+
     /// Use to control write rate of flush and compaction. Flush has higher
     /// priority than compaction.
     /// If rate limiter is enabled, bytes_per_sync is set to 1MB by default.
@@ -1967,7 +2765,7 @@ impl ReadOptionsPy {
 
     /// Sets the lower bound for an iterator.
     pub fn set_iterate_lower_bound(&mut self, key: &PyAny, py: Python) -> PyResult<()> {
-        self.iterate_upper_bound = key.to_object(py);
+        self.iterate_lower_bound = key.to_object(py);
         Ok(())
     }
 
@@ -2095,11 +2893,37 @@ impl ReadOptionsPy {
         Ok(opt)
     }
 
-    pub(crate) fn to_read_opt(&self, raw_mode: bool, py: Python) -> PyResult<ReadOpt> {
+    pub(crate) fn to_read_opt(
+        &self,
+        raw_mode: bool,
+        namespaces: bool,
+        namespace: Option<&KeyNamespace>,
+        py: Python,
+    ) -> PyResult<ReadOpt> {
         let opt = unsafe { ReadOpt(librocksdb_sys::rocksdb_readoptions_create()) };
-        if !self.iterate_lower_bound.is_none(py) {
-            let lower_bound = encode_key(self.iterate_lower_bound.as_ref(py), raw_mode)?;
-
+        let lower_bound = match (self.iterate_lower_bound.is_none(py), namespace) {
+            (false, None) => {
+                Some(encode_key(self.iterate_lower_bound.as_ref(py), raw_mode)?.to_vec())
+            }
+            (false, Some(ns)) => {
+                Some(ns.key(&encode_key(self.iterate_lower_bound.as_ref(py), raw_mode)?))
+            }
+            (true, Some(ns)) => Some(ns.header().to_vec()),
+            (true, None) => None,
+        };
+        let upper_bound = match (self.iterate_upper_bound.is_none(py), namespace) {
+            (false, None) => {
+                Some(encode_key(self.iterate_upper_bound.as_ref(py), raw_mode)?.to_vec())
+            }
+            (false, Some(ns)) => {
+                Some(ns.key(&encode_key(self.iterate_upper_bound.as_ref(py), raw_mode)?))
+            }
+            (true, Some(ns)) => ns.upper_bound(),
+            (true, None) => root_upper_bound(namespaces),
+        };
+        let prefix_same_as_start =
+            self.prefix_same_as_start || namespace.map_or(false, |ns| ns.prefix_same_as_start());
+        if let Some(lower_bound) = lower_bound {
             unsafe {
                 librocksdb_sys::rocksdb_readoptions_set_iterate_lower_bound(
                     opt.0,
@@ -2108,9 +2932,7 @@ impl ReadOptionsPy {
                 );
             }
         }
-        if !self.iterate_upper_bound.is_none(py) {
-            let upper_bound = encode_key(self.iterate_upper_bound.as_ref(py), raw_mode)?;
-
+        if let Some(upper_bound) = upper_bound {
             unsafe {
                 librocksdb_sys::rocksdb_readoptions_set_iterate_upper_bound(
                     opt.0,
@@ -2123,7 +2945,7 @@ impl ReadOptionsPy {
             librocksdb_sys::rocksdb_readoptions_set_fill_cache(opt.0, self.fill_cache as c_uchar);
             librocksdb_sys::rocksdb_readoptions_set_prefix_same_as_start(
                 opt.0,
-                self.prefix_same_as_start as c_uchar,
+                prefix_same_as_start as c_uchar,
             );
             librocksdb_sys::rocksdb_readoptions_set_total_order_seek(
                 opt.0,
@@ -2188,11 +3010,18 @@ impl MemtableFactoryPy {
     }
 }
 
+impl BlockBasedOptionsPy {
+    fn set_metadata_pinning(&mut self, name: &str, tier: &PinningTierPy) {
+        self.1
+            .push(format!("metadata_cache_options={{{name}={}}}", tier.0));
+    }
+}
+
 #[pymethods]
 impl BlockBasedOptionsPy {
     #[new]
     pub fn default() -> Self {
-        BlockBasedOptionsPy(BlockBasedOptions::default())
+        BlockBasedOptionsPy(BlockBasedOptions::default(), Vec::new())
     }
 
     /// Approximate size of user data packed per block. Note that the
@@ -2243,10 +3072,121 @@ impl BlockBasedOptionsPy {
         self.0.set_bloom_filter(bits_per_key, block_based)
     }
 
+    /// Sets a [Ribbon filter](http://rocksdb.org/blog/2021/12/29/ribbon-filter.html)
+    /// policy to reduce disk reads.
+    ///
+    /// Ribbon filters use about 30% less memory than Bloom filters with the same
+    /// false positive rate, for more CPU when building them.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import BlockBasedOptions, Options
+    ///
+    ///         opts = Options()
+    ///         block_opts = BlockBasedOptions()
+    ///         block_opts.set_ribbon_filter(10.0)
+    ///         opts.set_block_based_table_factory(block_opts)
+    ///
+    /// Args:
+    ///     bloom_equivalent_bits_per_key: the bits per key of the Bloom filter
+    ///         with the same false positive rate.
+    pub fn set_ribbon_filter(&mut self, bloom_equivalent_bits_per_key: c_double) {
+        self.0.set_ribbon_filter(bloom_equivalent_bits_per_key)
+    }
+
+    /// Sets a hybrid Bloom/Ribbon filter policy to reduce disk reads:
+    /// Bloom filters are used before `bloom_before_level`, and Ribbon
+    /// filters for the other levels.
+    ///
+    /// This combines the memory savings of Ribbon filters in the
+    /// largest levels with the lower CPU usage of Bloom filters for the
+    /// short-lived files of the first levels.
+    ///
+    /// Args:
+    ///     bloom_equivalent_bits_per_key: the bits per key of the Bloom filter
+    ///         with the same false positive rate.
+    ///     bloom_before_level: the first level using Ribbon filters.
+    pub fn set_hybrid_ribbon_filter(
+        &mut self,
+        bloom_equivalent_bits_per_key: c_double,
+        bloom_before_level: c_int,
+    ) {
+        self.0
+            .set_hybrid_ribbon_filter(bloom_equivalent_bits_per_key, bloom_before_level)
+    }
+
+    /// If false, only the prefixes of the keys (see `Options.set_prefix_extractor`)
+    /// are added to the filters, not whole keys: smaller filters, which only
+    /// help prefix seeks.
+    ///
+    /// Default: true.
+    pub fn set_whole_key_filtering(&mut self, v: bool) {
+        self.0.set_whole_key_filtering(v)
+    }
+
+    /// If true, generate Bloom/Ribbon filters that minimize the internal
+    /// fragmentation of the memory allocator, to save memory.
+    ///
+    /// Default: false.
+    pub fn set_optimize_filters_for_memory(&mut self, v: bool) {
+        self.0.set_optimize_filters_for_memory(v)
+    }
+
     pub fn set_cache_index_and_filter_blocks(&mut self, v: bool) {
         self.0.set_cache_index_and_filter_blocks(v)
     }
 
+    /// If cache_index_and_filter_blocks is enabled, cache index and filter
+    /// blocks with high priority. With a `Cache` having a high priority pool,
+    /// index, filter and compression dictionary blocks are then less likely
+    /// to be evicted than data blocks.
+    ///
+    /// Applied when these options are set with
+    /// `Options.set_block_based_table_factory`.
+    ///
+    /// Default: true.
+    pub fn set_cache_index_and_filter_blocks_with_high_priority(&mut self, v: bool) {
+        self.1.push(format!(
+            "cache_index_and_filter_blocks_with_high_priority={v}"
+        ));
+    }
+
+    /// The tier of top-level index and filter blocks (of partitioned
+    /// indexes and filters) that are pinned in the block cache.
+    ///
+    /// Applied when these options are set with
+    /// `Options.set_block_based_table_factory`.
+    ///
+    /// Default: `PinningTier.fallback()`, which follows
+    /// `set_pin_top_level_index_and_filter`.
+    pub fn set_top_level_index_pinning_tier(&mut self, tier: &PinningTierPy) {
+        self.set_metadata_pinning("top_level_index_pinning", tier)
+    }
+
+    /// The tier of index and filter partitions that are pinned in the block cache.
+    ///
+    /// Applied when these options are set with
+    /// `Options.set_block_based_table_factory`.
+    ///
+    /// Default: `PinningTier.fallback()`, which follows
+    /// `set_pin_l0_filter_and_index_blocks_in_cache`.
+    pub fn set_partition_pinning_tier(&mut self, tier: &PinningTierPy) {
+        self.set_metadata_pinning("partition_pinning", tier)
+    }
+
+    /// The tier of unpartitioned index, filter and compression dictionary
+    /// blocks that are pinned in the block cache.
+    ///
+    /// Applied when these options are set with
+    /// `Options.set_block_based_table_factory`.
+    ///
+    /// Default: `PinningTier.fallback()`, which follows
+    /// `set_pin_l0_filter_and_index_blocks_in_cache`.
+    pub fn set_unpartitioned_pinning_tier(&mut self, tier: &PinningTierPy) {
+        self.set_metadata_pinning("unpartitioned_pinning", tier)
+    }
+
     /// Defines the index type to be used for SS-table lookups.
     ///
     /// Example:
@@ -2459,7 +3399,7 @@ impl CachePy {
     /// Create a lru cache with capacity
     #[new]
     pub fn new_lru_cache(capacity: size_t) -> CachePy {
-        CachePy(Cache::new_lru_cache(capacity))
+        CachePy(Cache::new_lru_cache(capacity), capacity)
     }
 
     /// Creates a HyperClockCache with capacity in bytes.
@@ -2484,10 +3424,10 @@ impl CachePy {
     /// errs towards this side.
     #[staticmethod]
     pub fn new_hyper_clock_cache(capacity: size_t, estimated_entry_charge: size_t) -> CachePy {
-        CachePy(Cache::new_hyper_clock_cache(
+        CachePy(
+            Cache::new_hyper_clock_cache(capacity, estimated_entry_charge),
             capacity,
-            estimated_entry_charge,
-        ))
+        )
     }
 
     /// Returns the Cache memory usage
@@ -2502,7 +3442,54 @@ impl CachePy {
 
     /// Sets cache capacity
     pub fn set_capacity(&mut self, capacity: size_t) {
-        self.0.set_capacity(capacity)
+        self.0.set_capacity(capacity);
+        self.1 = capacity;
+    }
+
+    /// Returns the cache capacity
+    pub fn get_capacity(&self) -> usize {
+        self.1
+    }
+}
+
+#[pymethods]
+impl WriteBufferManagerPy {
+    #[new]
+    #[pyo3(signature = (buffer_size, allow_stall = false, cache = None))]
+    pub fn new(buffer_size: size_t, allow_stall: bool, cache: Option<&CachePy>) -> Self {
+        WriteBufferManagerPy(match cache {
+            None => WriteBufferManager::new_write_buffer_manager(buffer_size, allow_stall),
+            Some(cache) => WriteBufferManager::new_write_buffer_manager_with_cache(
+                buffer_size,
+                allow_stall,
+                cache.0.clone(),
+            ),
+        })
+    }
+
+    /// Returns the memory usage of the memtables, in bytes.
+    pub fn usage(&self) -> usize {
+        self.0.get_usage()
+    }
+
+    /// Returns the memory limit of the memtables, in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.0.get_buffer_size()
+    }
+
+    /// Sets the memory limit of the memtables, in bytes.
+    pub fn set_buffer_size(&self, buffer_size: size_t) {
+        self.0.set_buffer_size(buffer_size)
+    }
+
+    /// Returns whether the memory of the memtables is limited (`buffer_size > 0`).
+    pub fn enabled(&self) -> bool {
+        self.0.enabled()
+    }
+
+    /// Sets whether writes stall when the memory usage exceeds `buffer_size`.
+    pub fn set_allow_stall(&self, allow_stall: bool) {
+        self.0.set_allow_stall(allow_stall)
     }
 }
 
@@ -2637,6 +3624,57 @@ impl DBCompactionStylePy {
     }
 }
 
+#[pymethods]
+impl DBCompactionPriPy {
+    #[staticmethod]
+    pub fn by_compensated_size() -> Self {
+        DBCompactionPriPy("kByCompensatedSize")
+    }
+
+    #[staticmethod]
+    pub fn oldest_largest_seq_first() -> Self {
+        DBCompactionPriPy("kOldestLargestSeqFirst")
+    }
+
+    #[staticmethod]
+    pub fn oldest_smallest_seq_first() -> Self {
+        DBCompactionPriPy("kOldestSmallestSeqFirst")
+    }
+
+    #[staticmethod]
+    pub fn min_overlapping_ratio() -> Self {
+        DBCompactionPriPy("kMinOverlappingRatio")
+    }
+
+    #[staticmethod]
+    pub fn round_robin() -> Self {
+        DBCompactionPriPy("kRoundRobin")
+    }
+}
+
+#[pymethods]
+impl PinningTierPy {
+    #[staticmethod]
+    pub fn fallback() -> Self {
+        PinningTierPy("kFallback")
+    }
+
+    #[staticmethod]
+    pub fn none() -> Self {
+        PinningTierPy("kNone")
+    }
+
+    #[staticmethod]
+    pub fn flushed_and_similar() -> Self {
+        PinningTierPy("kFlushedAndSimilar")
+    }
+
+    #[staticmethod]
+    pub fn all() -> Self {
+        PinningTierPy("kAll")
+    }
+}
+
 #[pymethods]
 impl ChecksumTypePy {
     #[staticmethod]
@@ -0,0 +1,212 @@
+use crate::codec::CodecPy;
+use crate::db_reference::{DbReference, DbReferenceHolder};
+use crate::encoder::encode_key;
+use crate::exceptions::{rocksdb_error, DbClosedError};
+use crate::namespace::KeyNamespace;
+use crate::{
+    RdictItems, RdictIter, RdictKeys, RdictValues, ReadOptionsPy, Snapshot, WriteOptionsPy,
+};
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PySlice;
+use rocksdb::{UnboundColumnFamily, WriteBatch, WriteOptions};
+use std::sync::Arc;
+
+/// A lazily evaluated view of the keys in `[start, stop)`,
+/// returned by slicing a `Rdict` or a `Snapshot`.
+///
+/// Nothing is read until the view is iterated. `start` and `stop`
+/// may be omitted for unbounded ranges, `step` is not supported.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict
+///
+///         db = Rdict("tmp")
+///         for i in range(100):
+///             db[i] = i * i
+///
+///         view = db[10:20]
+///         assert list(view) == list(range(10, 20))
+///         assert list(reversed(view)) == list(range(19, 9, -1))
+///         assert list(view.values()) == [i * i for i in range(10, 20)]
+///         assert view.count() == 10
+///
+///         # delete the keys 90 to 99
+///         db[90:].delete()
+///         assert db.count() == 90
+///
+///         del view, db
+///         Rdict.destroy("tmp")
+#[pyclass]
+pub(crate) struct RdictRange {
+    pub(crate) db: DbReferenceHolder,
+    pub(crate) column_family: Option<Arc<UnboundColumnFamily>>,
+    pub(crate) codec: CodecPy,
+    pub(crate) raw_mode: bool,
+    /// whether namespaces are enabled on the DB.
+    pub(crate) namespaces: bool,
+    /// read options of the parent, without iterate bounds.
+    pub(crate) read_opt: ReadOptionsPy,
+    pub(crate) start: PyObject,
+    pub(crate) end: PyObject,
+    pub(crate) namespace: Option<KeyNamespace>,
+    /// the snapshot to read from, if the view was taken from a snapshot.
+    pub(crate) snapshot: Option<Py<Snapshot>>,
+    /// column family and write options for `delete`,
+    /// `None` for read-only views (snapshots).
+    pub(crate) write: Option<(Arc<UnboundColumnFamily>, WriteOptionsPy)>,
+}
+
+/// the `(start, stop)` of a python slice, rejecting steps.
+pub(crate) fn slice_bounds(slice: &PySlice) -> PyResult<(PyObject, PyObject)> {
+    if !slice.getattr("step")?.is_none() {
+        return Err(PyValueError::new_err("slice step is not supported"));
+    }
+    Ok((
+        slice.getattr("start")?.into(),
+        slice.getattr("stop")?.into(),
+    ))
+}
+
+impl RdictRange {
+    fn get_db(&self) -> PyResult<&DbReference> {
+        self.db
+            .get()
+            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))
+    }
+
+    /// encode a key, within the namespace if any.
+    fn key_bytes(&self, key: &PyAny) -> PyResult<Vec<u8>> {
+        let key = encode_key(key, self.raw_mode)?;
+        Ok(match &self.namespace {
+            None => key.into_owned(),
+            Some(ns) => ns.key(&key),
+        })
+    }
+
+    /// An iterator bounded by `[start, end)`.
+    fn bounded_iter(&self, py: Python) -> PyResult<RdictIter> {
+        let mut read_opt = self.read_opt.clone();
+        if !self.start.is_none(py) {
+            read_opt.set_iterate_lower_bound(self.start.as_ref(py), py)?;
+        }
+        if !self.end.is_none(py) {
+            read_opt.set_iterate_upper_bound(self.end.as_ref(py), py)?;
+        }
+        RdictIter::new(
+            &self.db,
+            &self.column_family,
+            read_opt,
+            &self.codec,
+            self.raw_mode,
+            self.namespaces,
+            self.namespace.clone(),
+            self.snapshot.as_ref().map(|s| s.clone_ref(py)),
+            py,
+        )
+    }
+}
+
+#[pymethods]
+impl RdictRange {
+    /// Iterate through the keys of the range.
+    fn __iter__(&self, py: Python) -> PyResult<RdictKeys> {
+        RdictKeys::new(self.bounded_iter(py)?, false, None)
+    }
+
+    /// Iterate through the keys of the range, backwards.
+    fn __reversed__(&self, py: Python) -> PyResult<RdictKeys> {
+        RdictKeys::new(self.bounded_iter(py)?, true, None)
+    }
+
+    /// Iterate through the keys of the range.
+    ///
+    /// Args:
+    ///     backwards: iteration direction, forward if `False`.
+    #[pyo3(signature = (backwards = false))]
+    fn keys(&self, backwards: bool, py: Python) -> PyResult<RdictKeys> {
+        RdictKeys::new(self.bounded_iter(py)?, backwards, None)
+    }
+
+    /// Iterate through the values of the range.
+    ///
+    /// Args:
+    ///     backwards: iteration direction, forward if `False`.
+    #[pyo3(signature = (backwards = false))]
+    fn values(&self, backwards: bool, py: Python) -> PyResult<RdictValues> {
+        RdictValues::new(self.bounded_iter(py)?, backwards, None)
+    }
+
+    /// Iterate through the keys and values pairs of the range.
+    ///
+    /// Args:
+    ///     backwards: iteration direction, forward if `False`.
+    #[pyo3(signature = (backwards = false))]
+    fn items(&self, backwards: bool, py: Python) -> PyResult<RdictItems> {
+        RdictItems::new(self.bounded_iter(py)?, backwards, None)
+    }
+
+    /// Exact number of keys in the range, without decoding values.
+    fn count(&self, py: Python) -> PyResult<u64> {
+        let mut iter = self.bounded_iter(py)?;
+        let mut count = 0;
+        iter.seek_to_first();
+        while iter.valid() {
+            count += 1;
+            iter.next();
+        }
+        iter.status()?;
+        Ok(count)
+    }
+
+    /// Delete all the keys in the range (with `delete_range`).
+    ///
+    /// Notes:
+    ///     Views taken from a `Snapshot` are read-only.
+    fn delete(&self, py: Python) -> PyResult<()> {
+        let (cf, write_opt) = match &self.write {
+            None => return Err(PyException::new_err("cannot delete from a snapshot view")),
+            Some(write) => write,
+        };
+        let db = self.get_db()?;
+        let write_opt = WriteOptions::from(write_opt);
+        let mut iter = self.bounded_iter(py)?;
+        let begin = if self.start.is_none(py) {
+            iter.seek_to_first();
+            iter.status()?;
+            match iter.raw_key() {
+                None => return Ok(()),
+                Some(key) => key,
+            }
+        } else {
+            self.key_bytes(self.start.as_ref(py))?
+        };
+        if !self.end.is_none(py) {
+            let end = self.key_bytes(self.end.as_ref(py))?;
+            return db
+                .delete_range_cf_opt(cf, begin, end, &write_opt)
+                .map_err(rocksdb_error);
+        }
+        // unbounded end: delete up to the last key, then the last key itself
+        iter.seek_to_last();
+        iter.status()?;
+        let last = match iter.raw_key() {
+            None => return Ok(()),
+            Some(key) => key,
+        };
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(cf, begin, &last);
+        batch.delete_cf(cf, &last);
+        db.write_opt(batch, &write_opt).map_err(rocksdb_error)
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!(
+            "RdictRange(start={}, stop={})",
+            self.start.as_ref(py).repr()?,
+            self.end.as_ref(py).repr()?
+        ))
+    }
+}
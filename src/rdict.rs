@@ -1,25 +1,37 @@
+use crate::codec::{CodecPy, ValueCompressionPy, ValueCompressionType};
 use crate::db_reference::{DbReference, DbReferenceHolder};
 use crate::encoder::{decode_value, encode_key, encode_value};
-use crate::exceptions::DbClosedError;
+use crate::exceptions::{
+    ffi_error, rocksdb_error, DbClosedError, InvalidArgumentError, NotSupportedError, RocksDBError,
+};
 use crate::iter::{RdictItems, RdictKeys, RdictValues};
+use crate::namespace::{compare_keys, root_upper_bound, KeyNamespace, NAMESPACE_TAG};
+use crate::option_values::{
+    option_values, OptionScope, PersistedOptions, PERSISTED_ONLY_CF_OPTIONS,
+};
 use crate::options::{CachePy, EnvPy, SliceTransformType};
+use crate::range::{slice_bounds, RdictRange};
 use crate::{
-    CompactOptionsPy, FlushOptionsPy, IngestExternalFileOptionsPy, OptionsPy, RdictIter,
+    CompactOptionsPy, FlushOptionsPy, IngestExternalFileOptionsPy, OptionsPy, RdictIter, ReadOpt,
     ReadOptionsPy, Snapshot, WriteBatchPy, WriteOptionsPy,
 };
-use pyo3::exceptions::{PyException, PyKeyError};
+use libc::{c_char, c_int, size_t};
+use pyo3::exceptions::{PyException, PyKeyError, PyTypeError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList, PySlice, PyTuple};
 use rocksdb::{
-    ColumnFamilyDescriptor, FlushOptions, LiveFile, ReadOptions, UnboundColumnFamily, WriteOptions,
-    DEFAULT_COLUMN_FAMILY_NAME,
+    AsColumnFamilyRef, ColumnFamilyDescriptor, ColumnFamilyTtl, FlushOptions, LiveFile,
+    ReadOptions, UnboundColumnFamily, WriteBatch, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::ptr::null_mut;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
@@ -56,22 +68,41 @@ type DB = rocksdb::DBWithThreadMode<rocksdb::MultiThreaded>;
 ///     column_families (dict): (name, options) pairs, these `Options`
 ///         must have the same `raw_mode` argument as the main `Options`.
 ///         A column family called 'default' is always created.
+///         The options can also be an `(Options, ttl_seconds)` tuple:
+///         entries of that column family are then dropped by compactions
+///         once older than `ttl_seconds`, and the DB is opened with TTL
+///         (see `AccessType.with_ttl`; other column families never expire
+///         unless `with_ttl` is used). These ttls are kept in the rocksdict
+///         config, so the DB is opened with them again when reopened,
+///         and an `(Options, None)` tuple removes the ttl. Values of DBs
+///         with ttl carry a timestamp: ttls can only be set on DBs created
+///         with one, and such DBs cannot be opened as read_only or secondary.
 ///     access_type (AccessType): there are four access types:
 ///         ReadWrite, ReadOnly, WithTTL, and Secondary, use
 ///         AccessType class to create.
+///     open_all_column_families (bool): open every existing column family
+///         (listed with `Rdict.list_cf`), even those missing from
+///         `column_families`, with the options persisted in the OPTIONS
+///         file and the rocksdict config (e.g. prefix extractors).
+///     verify_options (bool): raise `InvalidArgumentError` before opening
+///         if the passed options are incompatible with the persisted ones
+///         (comparator, `raw_mode`, prefix extractor or table factory),
+///         see `Options.check_compatibility`.
 #[pyclass(name = "Rdict")]
 pub(crate) struct Rdict {
     pub(crate) write_opt: WriteOptions,
     pub(crate) flush_opt: FlushOptionsPy,
     pub(crate) read_opt: ReadOptions,
-    pub(crate) loads: PyObject,
-    pub(crate) dumps: PyObject,
+    pub(crate) codec: CodecPy,
     pub(crate) write_opt_py: WriteOptionsPy,
     pub(crate) read_opt_py: ReadOptionsPy,
     pub(crate) column_family: Option<Arc<UnboundColumnFamily>>,
     pub(crate) opt_py: OptionsPy,
     pub(crate) access_type: AccessType,
     pub(crate) slice_transforms: Arc<RwLock<HashMap<String, SliceTransformType>>>,
+    pub(crate) codecs: Arc<RwLock<HashMap<String, CodecPy>>>,
+    pub(crate) ttls: Arc<RwLock<HashMap<String, u64>>>,
+    pub(crate) namespace: Option<KeyNamespace>,
     // drop DB last
     pub(crate) db: DbReferenceHolder,
 }
@@ -103,15 +134,78 @@ pub(crate) struct Rdict {
 #[pyclass(name = "AccessType")]
 pub(crate) struct AccessType(AccessTypeInner);
 
-#[derive(Serialize, Deserialize)]
+/// version of the rocksdict config file format
+pub const ROCKSDICT_CONFIG_VERSION: u32 = 1;
+/// version of the encoding of keys in non-raw mode
+pub const KEY_ENCODING_VERSION: u32 = 1;
+/// version of the encoding of keys in non-raw mode with namespaces enabled
+pub const NAMESPACED_KEY_ENCODING_VERSION: u32 = 2;
+/// name of the comparator of non-raw DBs
+pub(crate) const ROCKSDICT_COMPARATOR: &str = "rocksdict";
+/// name of the comparator of non-raw DBs with namespaces enabled: rocksdict
+/// versions without namespaces order namespaced keys differently
+pub(crate) const NAMESPACED_ROCKSDICT_COMPARATOR: &str = "rocksdict.v2";
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RocksDictConfig {
+    // version of the config file format, 0 for files written before it was added
+    #[serde(default)]
+    pub version: u32,
     pub raw_mode: bool,
     // mapping from column families to SliceTransformType
     pub prefix_extractors: HashMap<String, SliceTransformType>,
+    // mapping from column families to codec names
+    #[serde(default)]
+    pub codecs: HashMap<String, String>,
+    // mapping from column families to value compression
+    #[serde(default)]
+    pub value_compressions: HashMap<String, ValueCompressionType>,
+    // mapping from column families to `ttl` in seconds
+    #[serde(default)]
+    pub ttls: HashMap<String, u64>,
+    // name of the comparator of the DB
+    #[serde(default)]
+    pub comparator: String,
+    // version of the key encoding, 0 in raw mode
+    #[serde(default)]
+    pub key_encoding_version: u32,
+    // version of rocksdict that created the DB, `None` if unknown
+    #[serde(default)]
+    pub created_with: Option<String>,
+    // whether the DB was created with ttl, so that its values carry a timestamp
+    #[serde(default)]
+    pub ttl_timestamps: bool,
 }
 
 impl RocksDictConfig {
+    pub fn new(
+        raw_mode: bool,
+        namespaces: bool,
+        prefix_extractors: HashMap<String, SliceTransformType>,
+        codecs: HashMap<String, String>,
+        value_compressions: HashMap<String, ValueCompressionType>,
+        ttls: HashMap<String, u64>,
+    ) -> Self {
+        RocksDictConfig {
+            version: ROCKSDICT_CONFIG_VERSION,
+            raw_mode,
+            prefix_extractors,
+            codecs,
+            value_compressions,
+            ttls,
+            comparator: comparator_name(raw_mode, namespaces).to_string(),
+            key_encoding_version: key_encoding_version(raw_mode, namespaces),
+            created_with: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ttl_timestamps: false,
+        }
+    }
+
     pub fn load<P: AsRef<Path>>(path: P) -> PyResult<Self> {
+        RocksDictConfig::read(path)?.migrate()
+    }
+
+    /// read a config as written, without migration
+    fn read<P: AsRef<Path>>(path: P) -> PyResult<Self> {
         let config_file = fs::File::options().read(true).open(path)?;
         match serde_json::from_reader(config_file) {
             Ok(c) => Ok(c),
@@ -119,31 +213,306 @@ impl RocksDictConfig {
         }
     }
 
+    /// Write the config atomically: to a temporary file first,
+    /// which is synced and then renamed over `path`.
+    ///
+    /// The `created_with` version and `ttl_timestamps` of an existing config
+    /// are kept, and a config written by a newer version is not overwritten.
     pub fn save<P: AsRef<Path>>(&self, path: P) -> PyResult<()> {
-        let config_file = fs::File::options().create(true).write(true).open(path)?;
-        match serde_json::to_writer(config_file, self) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+        let path = path.as_ref();
+        let (created_with, ttl_timestamps) = match RocksDictConfig::read(path) {
+            Ok(existing) => {
+                let existing = existing.migrate()?;
+                (
+                    existing.created_with,
+                    existing.ttl_timestamps || self.ttl_timestamps,
+                )
+            }
+            Err(_) => (self.created_with.clone(), self.ttl_timestamps),
+        };
+        let content = serde_json::to_vec(&RocksDictConfig {
+            created_with,
+            ttl_timestamps,
+            ..self.clone()
+        })
+        .map_err(|e| PyException::new_err(e.to_string()))?;
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let mut tmp_file = fs::File::create(&tmp_path)?;
+        tmp_file.write_all(&content)?;
+        tmp_file.sync_all()?;
+        drop(tmp_file);
+        fs::rename(&tmp_path, path)?;
+        // persist the rename (not supported on every platform)
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if let Ok(dir) = fs::File::open(dir) {
+                let _ = dir.sync_all();
+            }
         }
+        Ok(())
+    }
+
+    /// upgrade a config written by an older version to `ROCKSDICT_CONFIG_VERSION`
+    fn migrate(mut self) -> PyResult<Self> {
+        if self.version > ROCKSDICT_CONFIG_VERSION {
+            return Err(PyException::new_err(format!(
+                "rocksdict config version {} is not supported (latest: {}), \
+                 the DB was written by a newer version of rocksdict",
+                self.version, ROCKSDICT_CONFIG_VERSION
+            )));
+        }
+        if self.version < 1 {
+            // written before namespaces were added
+            self.comparator = comparator_name(self.raw_mode, false).to_string();
+            self.key_encoding_version = key_encoding_version(self.raw_mode, false);
+            self.created_with = None;
+            self.version = 1;
+        }
+        Ok(self)
+    }
+}
+
+/// name of the comparator of a DB, as written in OPTIONS files
+pub(crate) fn comparator_name(raw_mode: bool, namespaces: bool) -> &'static str {
+    if raw_mode {
+        "leveldb.BytewiseComparator"
+    } else if namespaces {
+        NAMESPACED_ROCKSDICT_COMPARATOR
+    } else {
+        ROCKSDICT_COMPARATOR
+    }
+}
+
+fn key_encoding_version(raw_mode: bool, namespaces: bool) -> u32 {
+    if raw_mode {
+        0
+    } else if namespaces {
+        NAMESPACED_KEY_ENCODING_VERSION
+    } else {
+        KEY_ENCODING_VERSION
     }
 }
 
 impl Rdict {
     fn dump_config(&self) -> PyResult<()> {
         let config_path = config_file(&self.path()?);
-        RocksDictConfig {
-            raw_mode: self.opt_py.raw_mode,
-            prefix_extractors: self.slice_transforms.read().unwrap().clone(),
-        }
+        RocksDictConfig::new(
+            self.opt_py.raw_mode,
+            self.opt_py.namespaces,
+            self.slice_transforms.read().unwrap().clone(),
+            codec_names(&self.codecs.read().unwrap()),
+            value_compressions(&self.codecs.read().unwrap()),
+            self.ttls.read().unwrap().clone(),
+        )
         .save(config_path)
     }
 
+    /// name of the current column family
+    fn column_family_name(&self) -> String {
+        match &self.column_family {
+            None => DEFAULT_COLUMN_FAMILY_NAME.to_string(),
+            Some(cf) => cf_name(cf),
+        }
+    }
+
     #[inline]
     fn get_db(&self) -> PyResult<&DbReference> {
         self.db
             .get()
             .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))
     }
+
+    /// handle of the current column family
+    #[inline]
+    fn current_cf(&self) -> PyResult<Arc<UnboundColumnFamily>> {
+        match &self.column_family {
+            None => Ok(self
+                .get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                .cf),
+            Some(cf) => Ok(cf.clone()),
+        }
+    }
+
+    /// encode a key, within the namespace if any
+    fn key_bytes<'a>(&self, key: &'a PyAny) -> PyResult<Cow<'a, [u8]>> {
+        let key = encode_key(key, self.opt_py.raw_mode)?;
+        Ok(match &self.namespace {
+            None => key,
+            Some(ns) => Cow::Owned(ns.key(&key)),
+        })
+    }
+
+    /// decode a key, stripping the namespace if any
+    fn decode_key(&self, py: Python, key: &[u8]) -> PyResult<PyObject> {
+        let key = match &self.namespace {
+            None => key,
+            Some(ns) => ns.strip(key),
+        };
+        decode_value(py, key, &self.codec, self.opt_py.raw_mode)
+    }
+
+    /// the smallest key after the keys of this namespace
+    /// (or after the keys outside of any namespace)
+    fn upper_bound(&self) -> Option<Vec<u8>> {
+        match &self.namespace {
+            None => root_upper_bound(self.opt_py.namespaces),
+            Some(ns) => ns.upper_bound(),
+        }
+    }
+
+    /// read options bounded to the namespace (if any)
+    fn bounded_read_options(&self) -> ReadOptions {
+        let mut opt = ReadOptions::default();
+        if let Some(ns) = &self.namespace {
+            opt.set_iterate_lower_bound(ns.header().to_vec());
+        }
+        if let Some(upper) = self.upper_bound() {
+            opt.set_iterate_upper_bound(upper);
+        }
+        opt
+    }
+
+    /// the first or last encoded key of the current column family (or namespace)
+    fn edge_key(&self, last: bool) -> PyResult<Option<Box<[u8]>>> {
+        let db = self.get_db()?;
+        let mut iter = db.raw_iterator_cf_opt(&self.current_cf()?, self.bounded_read_options());
+        if last {
+            iter.seek_to_last();
+        } else {
+            iter.seek_to_first();
+        }
+        iter.status().map_err(rocksdb_error)?;
+        Ok(iter.key().map(Box::from))
+    }
+
+    /// Count the encoded keys in `[start, end)` that start with `prefix`.
+    ///
+    /// Values are never decoded.
+    fn scan_range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        prefix: Option<&[u8]>,
+    ) -> PyResult<u64> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        // stay within the namespace
+        let upper = self.upper_bound();
+        let start = start.or_else(|| self.namespace.as_ref().map(|ns| ns.header()));
+        let end = end.or(upper.as_deref());
+        let opt = unsafe { ReadOpt(librocksdb_sys::rocksdb_readoptions_create()) };
+        unsafe {
+            if let Some(start) = start {
+                librocksdb_sys::rocksdb_readoptions_set_iterate_lower_bound(
+                    opt.0,
+                    start.as_ptr() as *const c_char,
+                    start.len() as size_t,
+                );
+            }
+            if let Some(end) = end {
+                librocksdb_sys::rocksdb_readoptions_set_iterate_upper_bound(
+                    opt.0,
+                    end.as_ptr() as *const c_char,
+                    end.len() as size_t,
+                );
+            }
+            librocksdb_sys::rocksdb_readoptions_set_fill_cache(opt.0, 0);
+            librocksdb_sys::rocksdb_readoptions_set_total_order_seek(opt.0, 1);
+        }
+        let mut count = 0u64;
+        unsafe {
+            let iter = librocksdb_sys::rocksdb_create_iterator_cf(db.inner(), opt.0, cf.inner());
+            match (start, prefix) {
+                (Some(start), Some(prefix)) if start > prefix => librocksdb_sys::rocksdb_iter_seek(
+                    iter,
+                    start.as_ptr() as *const c_char,
+                    start.len() as size_t,
+                ),
+                (_, Some(prefix)) => librocksdb_sys::rocksdb_iter_seek(
+                    iter,
+                    prefix.as_ptr() as *const c_char,
+                    prefix.len() as size_t,
+                ),
+                (_, None) => librocksdb_sys::rocksdb_iter_seek_to_first(iter),
+            }
+            while librocksdb_sys::rocksdb_iter_valid(iter) != 0 {
+                let mut key_len: size_t = 0;
+                let key = librocksdb_sys::rocksdb_iter_key(iter, &mut key_len);
+                let key = std::slice::from_raw_parts(key as *const u8, key_len);
+                if let Some(prefix) = prefix {
+                    if !key.starts_with(prefix) {
+                        break;
+                    }
+                }
+                count += 1;
+                librocksdb_sys::rocksdb_iter_next(iter);
+            }
+            let mut err: *mut c_char = null_mut();
+            librocksdb_sys::rocksdb_iter_get_error(iter, &mut err);
+            librocksdb_sys::rocksdb_iter_destroy(iter);
+            if !err.is_null() {
+                return Err(ffi_error(err));
+            }
+        }
+        Ok(count)
+    }
+
+    /// encode an optional (`None` for unbounded) key
+    fn encode_bound(&self, key: Option<&PyAny>) -> PyResult<Option<Box<[u8]>>> {
+        match key {
+            Some(key) if !key.is_none() => Ok(Some(self.key_bytes(key)?.into())),
+            _ => Ok(None),
+        }
+    }
+
+    /// compare two encoded keys with the comparator of the database
+    fn compare_keys(&self, k1: &[u8], k2: &[u8]) -> Ordering {
+        if self.opt_py.raw_mode {
+            k1.cmp(k2)
+        } else {
+            compare_keys(k1, k2)
+        }
+    }
+
+    /// properties of the live files of the current column family, by file name,
+    /// only keeping the files overlapping with one of `ranges` if any.
+    fn tables_properties(&self, py: Python, ranges: Option<&[KeyRange]>) -> PyResult<PyObject> {
+        let db = self.get_db()?;
+        let cf_name = self.column_family_name();
+        let lower_bound = self.namespace.as_ref().map(|ns| ns.header().to_vec());
+        let upper_bound = self.upper_bound();
+        let overlaps = |lf: &LiveFile, (start, end): &KeyRange| {
+            let start = start.as_deref().or(lower_bound.as_deref());
+            let end = end.as_deref().or(upper_bound.as_deref());
+            let after_start = match (start, &lf.end_key) {
+                (Some(start), Some(largest)) => self.compare_keys(largest, start).is_ge(),
+                _ => true,
+            };
+            let before_end = match (end, &lf.start_key) {
+                (Some(end), Some(smallest)) => self.compare_keys(smallest, end).is_lt(),
+                _ => true,
+            };
+            after_start && before_end
+        };
+        let result = PyDict::new(py);
+        for lf in db.live_files().map_err(rocksdb_error)? {
+            if lf.column_family_name != cf_name {
+                continue;
+            }
+            if let Some(ranges) = ranges {
+                if !ranges.iter().any(|range| overlaps(&lf, range)) {
+                    continue;
+                }
+            }
+            let name = lf.name.trim_start_matches('/').to_string();
+            result.set_item(
+                name,
+                display_live_file_dict(lf, py, &self.codec, self.opt_py.raw_mode)?,
+            )?;
+        }
+        Ok(result.to_object(py))
+    }
 }
 
 #[pymethods]
@@ -158,16 +527,34 @@ impl Rdict {
         path,
         options = None,
         column_families = None,
-        access_type = AccessType::read_write()
+        access_type = AccessType::read_write(),
+        open_all_column_families = false,
+        verify_options = false
     ))]
     fn new(
         path: &str,
         options: Option<OptionsPy>,
-        column_families: Option<HashMap<String, OptionsPy>>,
+        column_families: Option<HashMap<String, ColumnFamilyOptions>>,
         access_type: AccessType,
+        open_all_column_families: bool,
+        verify_options: bool,
         py: Python,
     ) -> PyResult<Self> {
-        let pickle = PyModule::import(py, "pickle")?.to_object(py);
+        let mut cf_ttls = HashMap::new();
+        let column_families: Option<HashMap<String, OptionsPy>> = column_families.map(|cfs| {
+            cfs.into_iter()
+                .map(|(name, opt)| {
+                    let (opt, ttl) = opt.into_options();
+                    if let Some(ttl) = ttl {
+                        cf_ttls.insert(name.clone(), ttl);
+                    }
+                    (name, opt)
+                })
+                .collect()
+        });
+        if verify_options {
+            OptionsPy::verify_compatibility(path, options.as_ref(), column_families.as_ref(), py)?;
+        }
         // create db path if missing
         fs::create_dir_all(path).map_err(|e| PyException::new_err(e.to_string()))?;
         // load options
@@ -178,7 +565,7 @@ impl Rdict {
             CachePy::new_lru_cache(DEFAULT_LRU_CACHE_SIZE),
         );
         // prioritize passed options over loaded options
-        let (options, column_families) = match (options_loaded, options, column_families) {
+        let (mut options, column_families) = match (options_loaded, options, column_families) {
             (Ok((opt_loaded, cols_loaded)), opt, cols) => match (opt, cols) {
                 (Some(opt), Some(cols)) => (opt, Some(cols)),
                 (Some(opt), None) => (opt, Some(cols_loaded)),
@@ -191,34 +578,100 @@ impl Rdict {
                 (OptionsPy::new(false), cols)
             }
         };
-        // save slice transforms types in rocksdict config
+        // must be done before the rocksdict config is overwritten
+        let mut column_families = if open_all_column_families {
+            Some(all_column_families(path, &options, column_families)?)
+        } else {
+            column_families
+        };
+        // DBs created with namespaces enabled keep them
+        if !options.raw_mode && has_namespaced_comparator(path)? {
+            options.set_enable_namespaces(true);
+        }
+        if options.namespaces {
+            for opt in column_families.iter_mut().flat_map(|cfs| cfs.values_mut()) {
+                opt.set_enable_namespaces(true);
+            }
+        }
+        // save slice transforms types, codecs and ttls in rocksdict config
         let config_path = config_file(path);
         let mut prefix_extractors = HashMap::new();
+        let mut codecs = HashMap::new();
+        let persisted_config = RocksDictConfig::load(&config_path).ok();
+        // values of DBs created with ttl carry a timestamp, which DBs created
+        // without it lack: the format of existing DBs cannot change
+        let db_exists = Path::new(path).join("CURRENT").exists();
+        let ttl_timestamps = match &persisted_config {
+            Some(config) => config.ttl_timestamps,
+            None => false,
+        };
+        // keep persisted ttls unless new ones are set, a `None` ttl removes it
+        let mut ttls = persisted_config.map(|c| c.ttls).unwrap_or_default();
+        for (name, ttl) in cf_ttls {
+            match ttl {
+                Some(ttl) => {
+                    if db_exists && !ttl_timestamps {
+                        return Err(InvalidArgumentError::new_err(format!(
+                            "cannot set a ttl on column family {name}: \
+                             the DB was created without ttl"
+                        )));
+                    }
+                    ttls.insert(name, ttl);
+                }
+                None => {
+                    ttls.remove(&name);
+                }
+            }
+        }
+        let uses_ttl = ttl_timestamps
+            || !ttls.is_empty()
+            || matches!(access_type.0, AccessTypeInner::WithTTL { .. });
+        if uses_ttl
+            && matches!(
+                access_type.0,
+                AccessTypeInner::ReadOnly { .. } | AccessTypeInner::Secondary { .. }
+            )
+        {
+            return Err(NotSupportedError::new_err(
+                "DBs with ttl cannot be opened as read_only or secondary",
+            ));
+        }
         if let Some(slice_transform) = &options.prefix_extractor {
             prefix_extractors.insert(
                 DEFAULT_COLUMN_FAMILY_NAME.to_string(),
                 slice_transform.clone(),
             );
         }
+        if let Some(codec) = options.value_codec(py)? {
+            codecs.insert(DEFAULT_COLUMN_FAMILY_NAME.to_string(), codec);
+        }
         if let Some(cf) = &column_families {
             for (name, opt) in cf.iter() {
                 if let Some(slice_transform) = &opt.prefix_extractor {
                     prefix_extractors.insert(name.clone(), slice_transform.clone());
                 }
+                if let Some(codec) = opt.value_codec(py)? {
+                    codecs.insert(name.clone(), codec);
+                }
             }
         }
-        let rocksdict_config = RocksDictConfig {
-            raw_mode: options.raw_mode,
-            prefix_extractors: prefix_extractors.clone(),
-        };
-        rocksdict_config.save(config_path)?;
+        let mut rocksdict_config = RocksDictConfig::new(
+            options.raw_mode,
+            options.namespaces,
+            prefix_extractors.clone(),
+            codec_names(&codecs),
+            value_compressions(&codecs),
+            ttls.clone(),
+        );
+        rocksdict_config.ttl_timestamps = uses_ttl && (ttl_timestamps || !db_exists);
         let opt_inner = &options.inner_opt;
         // define column families
         let cfs = match column_families {
             None => {
-                vec![ColumnFamilyDescriptor::new(
+                vec![cf_descriptor(
                     DEFAULT_COLUMN_FAMILY_NAME,
                     opt_inner.clone(),
+                    &ttls,
                 )]
             }
             Some(cf) => {
@@ -237,13 +690,14 @@ impl Rdict {
                 }
                 let mut cfs = cf
                     .into_iter()
-                    .map(|(name, opt)| ColumnFamilyDescriptor::new(name, opt.inner_opt))
+                    .map(|(name, opt)| cf_descriptor(&name, opt.inner_opt, &ttls))
                     .collect::<Vec<_>>();
                 // automatically add default column families
                 if !has_default_cf {
-                    cfs.push(ColumnFamilyDescriptor::new(
+                    cfs.push(cf_descriptor(
                         DEFAULT_COLUMN_FAMILY_NAME,
                         opt_inner.clone(),
+                        &ttls,
                     ));
                 }
                 cfs
@@ -251,6 +705,10 @@ impl Rdict {
         };
         // open db
         let db = match &access_type.0 {
+            // column families without ttl never expire
+            AccessTypeInner::ReadWrite if uses_ttl => {
+                DB::open_cf_descriptors_with_ttl(opt_inner, path, cfs, Duration::ZERO)
+            }
             AccessTypeInner::ReadWrite => DB::open_cf_descriptors(opt_inner, path, cfs),
             AccessTypeInner::ReadOnly {
                 error_if_log_file_exist,
@@ -262,33 +720,96 @@ impl Rdict {
                 DB::open_cf_descriptors_with_ttl(opt_inner, path, cfs, *ttl)
             }
         }
-        .map_err(|e| PyException::new_err(e.to_string()))?;
+        .map_err(rocksdb_error)?;
+        rocksdict_config.save(config_path)?;
         let r_opt = ReadOptionsPy::default(py)?;
         let w_opt = WriteOptionsPy::new();
+        let codec = match codecs.get(DEFAULT_COLUMN_FAMILY_NAME) {
+            Some(codec) => codec.clone(),
+            None => CodecPy::pickle(py)?,
+        };
         Ok(Rdict {
             db: DbReferenceHolder::new(db),
             write_opt: (&w_opt).into(),
             flush_opt: FlushOptionsPy::new(),
             read_opt: r_opt.to_read_options(options.raw_mode, py)?,
-            loads: pickle.getattr(py, "loads")?,
-            dumps: pickle.getattr(py, "dumps")?,
+            codec,
             write_opt_py: w_opt,
             read_opt_py: r_opt,
             column_family: None,
             opt_py: options.clone(),
             access_type,
             slice_transforms: Arc::new(RwLock::new(prefix_extractors)),
+            codecs: Arc::new(RwLock::new(codecs)),
+            ttls: Arc::new(RwLock::new(ttls)),
+            namespace: None,
         })
     }
 
     /// set custom dumps function
     fn set_dumps(&mut self, dumps: PyObject) {
-        self.dumps = dumps
+        self.codec.dumps = Some(dumps)
     }
 
     /// set custom loads function
     fn set_loads(&mut self, loads: PyObject) {
-        self.loads = loads
+        self.codec.loads = Some(loads)
+    }
+
+    /// Set the codec of the current column family.
+    ///
+    /// The codec name is persisted in the rocksdict config file,
+    /// and is used by `get_column_family` and `get_column_family_handle`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Codec
+    ///
+    ///         db = Rdict("./codec_db")
+    ///         db.set_codec(Codec.msgpack())
+    ///         db["key"] = {"a": b"bytes", "b": [1, 2, 3]}
+    ///
+    /// Args:
+    ///     codec (Codec): `Codec.pickle()`, `Codec.json()`,
+    ///         `Codec.msgpack()` or `Codec.custom(name, dumps, loads)`.
+    fn set_codec(&mut self, codec: CodecPy) -> PyResult<()> {
+        self.codec = CodecPy {
+            compression: self.codec.compression.take(),
+            ..codec
+        };
+        self.codecs
+            .write()
+            .unwrap()
+            .insert(self.column_family_name(), self.codec.clone());
+        self.dump_config()
+    }
+
+    /// Set the value compression of the current column family,
+    /// `None` to disable it. Values already written stay readable.
+    ///
+    /// The setting is persisted in the rocksdict config file.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, ValueCompression
+    ///
+    ///         db = Rdict("./compressed_db")
+    ///         db.set_value_compression(ValueCompression.zstd(level=3, threshold=512))
+    ///         db["doc"] = {"text": "lorem ipsum " * 1000}
+    fn set_value_compression(&mut self, compression: Option<ValueCompressionPy>) -> PyResult<()> {
+        self.codec.compression = compression.map(|c| c.0);
+        self.codecs
+            .write()
+            .unwrap()
+            .insert(self.column_family_name(), self.codec.clone());
+        self.dump_config()
+    }
+
+    /// The codec of the current column family.
+    fn get_codec(&self) -> CodecPy {
+        self.codec.clone()
     }
 
     /// Optionally disable WAL or sync for this write.
@@ -328,7 +849,28 @@ impl Rdict {
     }
 
     /// Use list of keys for batch get.
+    ///
+    /// A slice (e.g. `db["a":"m"]`) returns a lazily evaluated
+    /// `RdictRange` view of the keys in `[start, stop)`.
     fn __getitem__(&self, key: &PyAny, py: Python) -> PyResult<PyObject> {
+        if let Ok(slice) = key.downcast::<PySlice>() {
+            let (start, end) = slice_bounds(slice)?;
+            let cf = self.current_cf()?;
+            let view = RdictRange {
+                db: self.db.clone(),
+                column_family: Some(cf.clone()),
+                codec: self.codec.clone(),
+                raw_mode: self.opt_py.raw_mode,
+                namespaces: self.opt_py.namespaces,
+                read_opt: self.read_opt_py.clone(),
+                start,
+                end,
+                namespace: self.namespace.clone(),
+                snapshot: None,
+                write: Some((cf, self.write_opt_py.clone())),
+            };
+            return Ok(Py::new(py, view)?.into_py(py));
+        }
         match self.get(key, None, None, py) {
             Ok(Some(v)) => Ok(v),
             Ok(None) => Err(PyKeyError::new_err(format!("key {key} not found"))),
@@ -371,24 +913,28 @@ impl Rdict {
             }
             Some(cf) => cf.clone(),
         };
-        if let Ok(keys) = PyTryFrom::try_from(key) {
+        if let Ok(key_list) = <PyList as PyTryFrom>::try_from(key) {
+            let mut keys: Vec<Cow<[u8]>> = Vec::with_capacity(key_list.len());
+            for key in key_list {
+                keys.push(self.key_bytes(key)?);
+            }
             return Ok(Some(
                 get_batch_inner(
                     db,
-                    keys,
+                    &keys,
                     py,
                     read_opt,
-                    &self.loads,
+                    &self.codec,
                     &cf,
                     self.opt_py.raw_mode,
                 )?
                 .to_object(py),
             ));
         }
-        let key_bytes = encode_key(key, self.opt_py.raw_mode)?;
+        let key_bytes = self.key_bytes(key)?;
         let value_result = db
             .get_pinned_cf_opt(&cf, key_bytes, read_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))?;
+            .map_err(rocksdb_error)?;
         match value_result {
             None => {
                 // try to return default value
@@ -401,7 +947,7 @@ impl Rdict {
             Some(slice) => Ok(Some(decode_value(
                 py,
                 slice.as_ref(),
-                &self.loads,
+                &self.codec,
                 self.opt_py.raw_mode,
             )?)),
         }
@@ -422,8 +968,8 @@ impl Rdict {
     #[pyo3(signature = (key, value, write_opt = None))]
     fn put(&self, key: &PyAny, value: &PyAny, write_opt: Option<&WriteOptionsPy>) -> PyResult<()> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
-        let value = encode_value(value, &self.dumps, self.opt_py.raw_mode)?;
+        let key = self.key_bytes(key)?;
+        let value = encode_value(value, &self.codec, self.opt_py.raw_mode)?;
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
             None => &self.write_opt,
@@ -434,12 +980,12 @@ impl Rdict {
         } else {
             db.put_opt(key, value, write_opt)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
     }
 
     fn __contains__(&self, key: &PyAny) -> PyResult<bool> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        let key = self.key_bytes(key)?;
         let may_exist = if let Some(cf) = &self.column_family {
             db.key_may_exist_cf_opt(cf, &key[..], &self.read_opt)
         } else {
@@ -456,7 +1002,7 @@ impl Rdict {
                     None => Ok(false),
                     Some(_) => Ok(true),
                 },
-                Err(e) => Err(PyException::new_err(e.to_string())),
+                Err(e) => Err(rocksdb_error(e)),
             }
         } else {
             Ok(false)
@@ -503,7 +1049,7 @@ impl Rdict {
         py: Python,
     ) -> PyResult<PyObject> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        let key = self.key_bytes(key)?;
         let read_opt_option = match read_opt {
             None => None,
             Some(opt) => Some(opt.to_read_options(self.opt_py.raw_mode, py)?),
@@ -529,7 +1075,7 @@ impl Rdict {
                 None => Ok((may, py.None()).to_object(py)),
                 Some(dat) => Ok((
                     may,
-                    decode_value(py, dat.as_ref(), &self.loads, self.opt_py.raw_mode)?,
+                    decode_value(py, dat.as_ref(), &self.codec, self.opt_py.raw_mode)?,
                 )
                     .to_object(py)),
             }
@@ -550,7 +1096,7 @@ impl Rdict {
     #[pyo3(signature = (key, write_opt = None))]
     fn delete(&self, key: &PyAny, write_opt: Option<&WriteOptionsPy>) -> PyResult<()> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        let key = self.key_bytes(key)?;
 
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
@@ -562,7 +1108,208 @@ impl Rdict {
         } else {
             db.delete_opt(key, write_opt)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
+    }
+
+    /// Number of keys in the current column family.
+    ///
+    /// Notes:
+    ///     This is exact: it scans all the keys (without decoding values),
+    ///     which takes time proportional to the size of the column family.
+    ///     Use `estimate_num_keys()` for a constant time estimation.
+    fn __len__(&self) -> PyResult<usize> {
+        Ok(self.scan_range(None, None, None)? as usize)
+    }
+
+    /// Iterate over the keys of the current column family.
+    fn __iter__(&self, py: Python) -> PyResult<RdictKeys> {
+        self.keys(false, None, None, py)
+    }
+
+    /// Compare with a mapping (e.g. `dict` or `Rdict`).
+    ///
+    /// Notes:
+    ///     This is exact: it reads all the keys and values of the
+    ///     current column family. Returns `NotImplemented` for objects
+    ///     that are not mappings.
+    fn __eq__(&self, other: &PyAny, py: Python) -> PyResult<PyObject> {
+        if !other.hasattr("keys")? || !other.hasattr("__getitem__")? {
+            return Ok(py.NotImplemented());
+        }
+        if other.len()? != self.__len__()? {
+            return Ok(false.to_object(py));
+        }
+        for item in self
+            .items(false, None, None, py)?
+            .into_py(py)
+            .as_ref(py)
+            .iter()?
+        {
+            let (key, value): (&PyAny, &PyAny) = item?.extract()?;
+            match other.get_item(key) {
+                Ok(other_value) if value.eq(other_value)? => {}
+                Ok(_) => return Ok(false.to_object(py)),
+                Err(e) if e.is_instance_of::<PyKeyError>(py) => return Ok(false.to_object(py)),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true.to_object(py))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        match &self.namespace {
+            None => Ok(format!(
+                "Rdict(path={:?}, column_family={:?})",
+                self.path()?,
+                self.column_family_name()
+            )),
+            Some(ns) => Ok(format!(
+                "Rdict(path={:?}, column_family={:?}, namespace={:?})",
+                self.path()?,
+                self.column_family_name(),
+                String::from_utf8_lossy(ns.prefix())
+            )),
+        }
+    }
+
+    /// Update the current column family from a mapping or an iterable
+    /// of key-value pairs, and keyword arguments, like `dict.update`.
+    ///
+    /// All the entries are written atomically in a single `WriteBatch`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./update_example")
+    ///         db.update({"a": 1, "b": 2})
+    ///         db.update([("c", 3)], d=4)
+    ///         assert dict(db.items()) == {"a": 1, "b": 2, "c": 3, "d": 4}
+    ///
+    ///         del db
+    ///         Rdict.destroy("./update_example")
+    #[pyo3(signature = (other = None, **kwargs))]
+    fn update(&self, other: Option<&PyAny>, kwargs: Option<&PyDict>) -> PyResult<()> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        let raw_mode = self.opt_py.raw_mode;
+        let mut batch = WriteBatch::default();
+        if let Some(other) = other {
+            if other.hasattr("keys")? {
+                for key in other.call_method0("keys")?.iter()? {
+                    let key = key?;
+                    let value = other.get_item(key)?;
+                    batch.put_cf(
+                        &cf,
+                        self.key_bytes(key)?,
+                        encode_value(value, &self.codec, raw_mode)?,
+                    );
+                }
+            } else {
+                for item in other.iter()? {
+                    let (key, value): (&PyAny, &PyAny) = item?.extract()?;
+                    batch.put_cf(
+                        &cf,
+                        self.key_bytes(key)?,
+                        encode_value(value, &self.codec, raw_mode)?,
+                    );
+                }
+            }
+        }
+        if let Some(kwargs) = kwargs {
+            for (key, value) in kwargs.iter() {
+                batch.put_cf(
+                    &cf,
+                    self.key_bytes(key)?,
+                    encode_value(value, &self.codec, raw_mode)?,
+                );
+            }
+        }
+        db.write_opt(batch, &self.write_opt).map_err(rocksdb_error)
+    }
+
+    /// Remove a key and return its value, like `dict.pop`.
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     default: returned if the key is not found,
+    ///         otherwise `KeyError` is raised.
+    #[pyo3(signature = (key, *default))]
+    fn pop(&self, key: &PyAny, default: &PyTuple, py: Python) -> PyResult<PyObject> {
+        if default.len() > 1 {
+            return Err(PyTypeError::new_err(format!(
+                "pop expected at most 2 arguments, got {}",
+                default.len() + 1
+            )));
+        }
+        match self.get(key, None, None, py)? {
+            Some(value) => {
+                self.delete(key, None)?;
+                Ok(value)
+            }
+            None => match default.get_item(0) {
+                Ok(default) => Ok(default.to_object(py)),
+                Err(_) => Err(PyKeyError::new_err(format!("key {key} not found"))),
+            },
+        }
+    }
+
+    /// Remove and return the `(key, value)` pair with the largest key.
+    ///
+    /// Raises `KeyError` if the column family is empty.
+    fn popitem(&self, py: Python) -> PyResult<PyObject> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        let mut iter = db.raw_iterator_cf_opt(&cf, self.bounded_read_options());
+        iter.seek_to_last();
+        iter.status().map_err(rocksdb_error)?;
+        match iter.item() {
+            None => Err(PyKeyError::new_err("popitem(): Rdict is empty")),
+            Some((key, value)) => {
+                let raw_mode = self.opt_py.raw_mode;
+                let item = (
+                    self.decode_key(py, key)?,
+                    decode_value(py, value, &self.codec, raw_mode)?,
+                );
+                db.delete_cf_opt(&cf, key, &self.write_opt)
+                    .map_err(rocksdb_error)?;
+                Ok(item.to_object(py))
+            }
+        }
+    }
+
+    /// Return the value of `key` if it exists, otherwise
+    /// insert `key` with the value `default` and return `default`.
+    #[pyo3(signature = (key, default = None))]
+    fn setdefault(&self, key: &PyAny, default: Option<&PyAny>, py: Python) -> PyResult<PyObject> {
+        match self.get(key, None, None, py)? {
+            Some(value) => Ok(value),
+            None => {
+                let default = default.unwrap_or_else(|| py.None().into_ref(py));
+                self.put(key, default, None)?;
+                Ok(default.to_object(py))
+            }
+        }
+    }
+
+    /// Remove all the entries of the current column family.
+    ///
+    /// Notes:
+    ///     This issues a single range deletion from the first
+    ///     to the last key (and a deletion of the last key).
+    ///     Use `compact_range(None, None)` to reclaim disk space.
+    fn clear(&self) -> PyResult<()> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        let (first, last) = match (self.edge_key(false)?, self.edge_key(true)?) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(&cf, &first, &last);
+        batch.delete_cf(&cf, &last);
+        db.write_opt(batch, &self.write_opt).map_err(rocksdb_error)
     }
 
     /// Reversible for iterating over keys and values.
@@ -618,8 +1365,11 @@ impl Rdict {
             &self.db,
             &self.column_family,
             read_opt,
-            &self.loads,
+            &self.codec,
             self.opt_py.raw_mode,
+            self.opt_py.namespaces,
+            self.namespace.clone(),
+            None,
             py,
         )
     }
@@ -717,7 +1467,7 @@ impl Rdict {
         } else {
             db.flush_opt(&f_opt)
         }
-        .map_err(|e| PyException::new_err(e.into_string()))
+        .map_err(rocksdb_error)
     }
 
     /// Flushes the WAL buffer. If `sync` is set to `true`, also syncs
@@ -725,8 +1475,7 @@ impl Rdict {
     #[pyo3(signature = (sync = true))]
     fn flush_wal(&self, sync: bool) -> PyResult<()> {
         let db = self.get_db()?;
-        db.flush_wal(sync)
-            .map_err(|e| PyException::new_err(e.into_string()))
+        db.flush_wal(sync).map_err(rocksdb_error)
     }
 
     /// Creates column family with given name and options.
@@ -738,7 +1487,12 @@ impl Rdict {
     /// Return:
     ///     the newly created column family
     #[pyo3(signature = (name, options = OptionsPy::new(false)))]
-    fn create_column_family(&self, name: &str, options: OptionsPy, py: Python) -> PyResult<Rdict> {
+    fn create_column_family(
+        &self,
+        name: &str,
+        mut options: OptionsPy,
+        py: Python,
+    ) -> PyResult<Rdict> {
         let db = self.get_db()?;
         if options.raw_mode != self.opt_py.raw_mode {
             return Err(PyException::new_err(format!(
@@ -746,24 +1500,29 @@ impl Rdict {
                 self.opt_py.raw_mode
             )));
         }
-        // write slice_transform info into config file
+        if self.opt_py.namespaces {
+            options.set_enable_namespaces(true);
+        }
+        // write slice_transform and codec info into config file
         if let Some(slice_transform) = options.prefix_extractor {
             self.slice_transforms
                 .write()
                 .unwrap()
                 .insert(name.to_string(), slice_transform);
         }
+        if let Some(codec) = options.value_codec(py)? {
+            self.codecs.write().unwrap().insert(name.to_string(), codec);
+        }
         self.dump_config()?;
         db.create_cf(name, &options.inner_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))?;
+            .map_err(rocksdb_error)?;
         self.get_column_family(name, py)
     }
 
     /// Drops the column family with the given name
     fn drop_column_family(&self, name: &str) -> PyResult<()> {
         let db = self.get_db()?;
-        db.drop_cf(name)
-            .map_err(|e| PyException::new_err(e.to_string()))
+        db.drop_cf(name).map_err(rocksdb_error)
     }
 
     /// Get a column family Rdict
@@ -785,18 +1544,104 @@ impl Rdict {
                 write_opt: (&self.write_opt_py).into(),
                 flush_opt: self.flush_opt,
                 read_opt: self.read_opt_py.to_read_options(self.opt_py.raw_mode, py)?,
-                loads: self.loads.clone(),
-                dumps: self.dumps.clone(),
+                codec: match self.codecs.read().unwrap().get(name) {
+                    Some(codec) => codec.clone(),
+                    None => self.codec.clone(),
+                },
                 column_family: Some(cf),
                 write_opt_py: self.write_opt_py.clone(),
                 read_opt_py: self.read_opt_py.clone(),
                 opt_py: self.opt_py.clone(),
                 access_type: self.access_type.clone(),
                 slice_transforms: self.slice_transforms.clone(),
+                codecs: self.codecs.clone(),
+                ttls: self.ttls.clone(),
+                namespace: None,
             }),
         }
     }
 
+    /// Get a view of the current column family where every key is
+    /// transparently prefixed by `prefix`.
+    ///
+    /// Namespaces are much lighter than column families: use them to
+    /// separate, for example, thousands of tenants in one column family.
+    /// `get`, `put`, `delete`, iteration, slicing, `delete_range`, `count`,
+    /// `clear` and so on only see the keys of the namespace, with the
+    /// prefix stripped. `WriteBatch` keys are not prefixed.
+    /// Except in raw mode, namespaced keys are not visible
+    /// when iterating over the `Rdict` itself.
+    ///
+    /// Notes:
+    ///     In raw mode, `prefix` is prepended to the keys as is.
+    ///     Otherwise the stored keys start with a 3 bytes header
+    ///     followed by `prefix`, so that namespaces never overlap.
+    ///     Iteration is bounded with `iterate_upper_bound`: iterators only
+    ///     use `prefix_same_as_start` if the prefix extractor of the column
+    ///     family extracts exactly that header (a fixed or capped prefix of
+    ///     `3 + len(prefix)` bytes), which in practice only holds when all the
+    ///     namespaces of the column family have prefixes of the same length.
+    ///
+    ///     Except in raw mode, namespaces must be enabled with
+    ///     `Options.set_enable_namespaces(True)` when the DB is created,
+    ///     otherwise `namespace` raises `NotSupportedError`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options()
+    ///         opt.set_enable_namespaces(True)
+    ///         db = Rdict("./namespace_example", opt)
+    ///         alice = db.namespace("alice")
+    ///         bob = db.namespace("bob")
+    ///         alice["balance"] = 100
+    ///         bob["balance"] = 50
+    ///         assert list(alice.items()) == [("balance", 100)]
+    ///         assert "balance" not in db
+    ///
+    ///         del alice, bob, db
+    ///         Rdict.destroy("./namespace_example")
+    ///
+    /// Args:
+    ///     prefix (str | bytes): the prefix of the namespace.
+    fn namespace(&self, prefix: &PyAny, py: Python) -> PyResult<Self> {
+        if self.namespace.is_some() {
+            return Err(PyException::new_err("namespaces cannot be nested"));
+        }
+        if !self.opt_py.raw_mode && !self.opt_py.namespaces {
+            return Err(NotSupportedError::new_err(
+                "namespaces are not enabled on this DB, \
+                 create it with `Options.set_enable_namespaces(True)`",
+            ));
+        }
+        let namespace = KeyNamespace::new(
+            prefix,
+            self.opt_py.raw_mode,
+            self.slice_transforms
+                .read()
+                .unwrap()
+                .get(&self.column_family_name()),
+        )?;
+        Ok(Self {
+            db: self.db.clone(),
+            write_opt: (&self.write_opt_py).into(),
+            flush_opt: self.flush_opt,
+            read_opt: self.read_opt_py.to_read_options(self.opt_py.raw_mode, py)?,
+            codec: self.codec.clone(),
+            column_family: self.column_family.clone(),
+            write_opt_py: self.write_opt_py.clone(),
+            read_opt_py: self.read_opt_py.clone(),
+            opt_py: self.opt_py.clone(),
+            access_type: self.access_type.clone(),
+            slice_transforms: self.slice_transforms.clone(),
+            codecs: self.codecs.clone(),
+            ttls: self.ttls.clone(),
+            namespace: Some(namespace),
+        })
+    }
+
     /// Use this method to obtain a ColumnFamily instance, which can be used in WriteBatch.
     ///
     /// Example:
@@ -821,6 +1666,7 @@ impl Rdict {
             Some(cf) => Ok(ColumnFamilyPy {
                 cf,
                 db: self.db.clone(),
+                codec: self.codecs.read().unwrap().get(name).cloned(),
             }),
         }
     }
@@ -864,11 +1710,17 @@ impl Rdict {
     /// Args:
     ///     paths: a list a paths
     ///     opts: IngestExternalFileOptionsPy instance
+    ///
+    /// Notes:
+    ///     Each call is atomic for the files of one column family only.
+    ///     Ingesting into several column families atomically
+    ///     (RocksDB's `IngestExternalFiles`) is not exposed by the
+    ///     RocksDB C API, hence not supported.
     #[pyo3(signature = (
         paths,
         opts = Python::with_gil(|py| Py::new(py, IngestExternalFileOptionsPy::new()).unwrap())
     ))]
-    fn ingest_external_file(
+    pub(crate) fn ingest_external_file(
         &self,
         paths: Vec<String>,
         opts: Py<IngestExternalFileOptionsPy>,
@@ -881,15 +1733,14 @@ impl Rdict {
         } else {
             db.ingest_external_file_opts(opts, paths)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
     }
 
     /// Tries to catch up with the primary by reading as much as possible from the
     /// log files.
     pub fn try_catch_up_with_primary(&self) -> PyResult<()> {
         let db = self.get_db()?;
-        db.try_catch_up_with_primary()
-            .map_err(|e| PyException::new_err(e.to_string()))
+        db.try_catch_up_with_primary().map_err(rocksdb_error)
     }
 
     /// Request stopping background work, if wait is true wait until it's done.
@@ -931,7 +1782,7 @@ impl Rdict {
             Some(opt) => opt,
         };
         db.write_opt(write_batch.consume()?, write_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)
     }
 
     /// Removes the database entries in the range `["from", "to")` of the current column family.
@@ -947,8 +1798,8 @@ impl Rdict {
         write_opt: Option<&WriteOptionsPy>,
     ) -> PyResult<()> {
         let db = self.get_db()?;
-        let from = encode_key(begin, self.opt_py.raw_mode)?;
-        let to = encode_key(end, self.opt_py.raw_mode)?;
+        let from = self.key_bytes(begin)?;
+        let to = self.key_bytes(end)?;
         let cf = match &self.column_family {
             None => {
                 self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
@@ -962,7 +1813,7 @@ impl Rdict {
             Some(opt) => opt,
         };
         db.delete_range_cf_opt(&cf, from, to, write_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)
     }
 
     /// Flush memory to disk, and drop the current column family.
@@ -998,9 +1849,9 @@ impl Rdict {
         self.db.close();
         match (flush_result, flush_wal_result) {
             (Ok(_), Ok(_)) => Ok(()),
-            (Err(e), Ok(_)) => Err(PyException::new_err(e.to_string())),
-            (Ok(_), Err(e)) => Err(PyException::new_err(e.to_string())),
-            (Err(e), Err(wal_e)) => Err(PyException::new_err(format!("{e}; {wal_e}"))),
+            (Err(e), Ok(_)) => Err(rocksdb_error(e)),
+            (Ok(_), Err(e)) => Err(rocksdb_error(e)),
+            (Err(e), Err(wal_e)) => Err(RocksDBError::new_err(format!("{e}; {wal_e}"))),
         }
     }
 
@@ -1025,14 +1876,14 @@ impl Rdict {
     ) -> PyResult<()> {
         let db = self.get_db()?;
         let from = if begin.is_none() {
-            None
+            self.namespace.as_ref().map(|ns| ns.header().to_vec())
         } else {
-            Some(encode_key(begin, self.opt_py.raw_mode)?)
+            Some(self.key_bytes(begin)?.to_vec())
         };
         let to = if end.is_none() {
-            None
+            self.namespace.as_ref().and_then(|ns| ns.upper_bound())
         } else {
-            Some(encode_key(end, self.opt_py.raw_mode)?)
+            Some(self.key_bytes(end)?.to_vec())
         };
         let opt = compact_opt.borrow(py);
         let opt_ref = opt.deref();
@@ -1055,7 +1906,52 @@ impl Rdict {
             None => db.set_options(&options),
             Some(cf) => db.set_options_cf(cf, &options),
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
+    }
+
+    /// Returns the live options of the DB and of the current column family
+    /// as a `dict` (see `Options.to_dict()`), including the changes made
+    /// by `set_options`.
+    ///
+    /// Unlike `Options.to_dict()`, it includes `ttl`, `compaction_pri`
+    /// and `bottommost_compression_opts` (as written in OPTIONS files).
+    ///
+    /// Notes:
+    ///     The options are read from the latest OPTIONS file of the DB,
+    ///     which RocksDB rewrites whenever options change.
+    fn get_options(&self, py: Python) -> PyResult<PyObject> {
+        let path = self.path()?;
+        let (db_options, cf_options) = OptionsPy::load_latest_inner(
+            &path,
+            EnvPy::default()?,
+            true,
+            CachePy::new_lru_cache(DEFAULT_LRU_CACHE_SIZE),
+        )?;
+        let cf_name = self.column_family_name();
+        let cf_options = match cf_options.get(&cf_name) {
+            Some(opt) if cf_name != DEFAULT_COLUMN_FAMILY_NAME => opt,
+            _ => &db_options,
+        };
+        let result = PyDict::new(py);
+        for (name, scope, value) in option_values(&db_options.inner_opt) {
+            if scope == OptionScope::Db {
+                result.set_item(name, value.to_object(py))?;
+            }
+        }
+        for (name, scope, value) in option_values(&cf_options.inner_opt) {
+            if scope == OptionScope::Cf {
+                result.set_item(name, value.to_object(py))?;
+            }
+        }
+        let persisted = PersistedOptions::load_latest(&path)?;
+        if let Some(persisted_cf) = persisted.as_ref().and_then(|p| p.cfs.get(&cf_name)) {
+            for (name, value) in PERSISTED_ONLY_CF_OPTIONS {
+                if let Some(persisted_value) = persisted_cf.get(name) {
+                    result.set_item(name, value.persisted_object(persisted_value, py))?;
+                }
+            }
+        }
+        Ok(result.to_object(py))
     }
 
     /// Retrieves a RocksDB property by name, for the current column family.
@@ -1065,7 +1961,7 @@ impl Rdict {
             None => db.property_value(name),
             Some(cf) => db.property_value_cf(cf, name),
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
     }
 
     /// Retrieves a RocksDB property and casts it to an integer
@@ -1079,7 +1975,109 @@ impl Rdict {
             None => db.property_int_value(name),
             Some(cf) => db.property_int_value_cf(cf, name),
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
+    }
+
+    /// Estimated number of keys in the current column family
+    /// (the `rocksdb.estimate-num-keys` property).
+    ///
+    /// Notes:
+    ///     The estimation is cheap but can be far off, since overwrites
+    ///     and deletions are only accounted for after compaction.
+    ///     Use `count()` or `len()` for an exact number.
+    fn estimate_num_keys(&self) -> PyResult<u64> {
+        Ok(self
+            .property_int_value("rocksdb.estimate-num-keys")?
+            .unwrap_or(0))
+    }
+
+    /// Exact number of keys in the current column family,
+    /// within `[start, end)` and starting with `prefix`.
+    ///
+    /// The scan is done in Rust, reading keys only,
+    /// without decoding values into python objects.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         db.update({"a": 1, "ab": 2, "b": 3, "c": 4})
+    ///         assert db.count() == 4
+    ///         assert db.count(start="ab", end="c") == 2
+    ///         assert db.count(prefix="a") == 2
+    ///
+    /// Args:
+    ///     start: the first key (included), `None` for unbounded.
+    ///     end: the last key (excluded), `None` for unbounded.
+    ///     prefix: only count keys of the same type starting with this
+    ///         str or bytes prefix. `None` to count all keys.
+    #[pyo3(signature = (start = None, end = None, prefix = None))]
+    fn count(
+        &self,
+        start: Option<&PyAny>,
+        end: Option<&PyAny>,
+        prefix: Option<&PyAny>,
+    ) -> PyResult<u64> {
+        let start = self.encode_bound(start)?;
+        let end = self.encode_bound(end)?;
+        let prefix = self.encode_bound(prefix)?;
+        self.scan_range(start.as_deref(), end.as_deref(), prefix.as_deref())
+    }
+
+    /// Approximate on-disk sizes (in bytes) of key ranges
+    /// in the current column family.
+    ///
+    /// Notes:
+    ///     Only data in SST files is taken into account,
+    ///     data still in memtables is not: call `flush()` first.
+    ///     The RocksDB C API has no approximate statistics of the
+    ///     memtables for a key range (`GetApproximateMemTableStats`),
+    ///     so there is no `approximate_memtable_stats`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         sizes = db.approximate_sizes([(0, 1000), (1000, 2000)])
+    ///
+    /// Args:
+    ///     ranges: a list of `(start, end)` tuples, `start` is included
+    ///         and `end` is excluded.
+    ///
+    /// Return:
+    ///     a list of sizes, one for each range.
+    fn approximate_sizes(&self, ranges: Vec<(&PyAny, &PyAny)>) -> PyResult<Vec<u64>> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        let mut starts = Vec::with_capacity(ranges.len());
+        let mut ends = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            starts.push(self.key_bytes(start)?);
+            ends.push(self.key_bytes(end)?);
+        }
+        let start_ptrs: Vec<*const c_char> =
+            starts.iter().map(|k| k.as_ptr() as *const c_char).collect();
+        let start_lens: Vec<size_t> = starts.iter().map(|k| k.len() as size_t).collect();
+        let end_ptrs: Vec<*const c_char> =
+            ends.iter().map(|k| k.as_ptr() as *const c_char).collect();
+        let end_lens: Vec<size_t> = ends.iter().map(|k| k.len() as size_t).collect();
+        let mut sizes = vec![0u64; starts.len()];
+        let mut err: *mut c_char = null_mut();
+        unsafe {
+            librocksdb_sys::rocksdb_approximate_sizes_cf(
+                db.inner(),
+                cf.inner(),
+                starts.len() as c_int,
+                start_ptrs.as_ptr(),
+                start_lens.as_ptr(),
+                end_ptrs.as_ptr(),
+                end_lens.as_ptr(),
+                sizes.as_mut_ptr(),
+                &mut err,
+            );
+        }
+        if !err.is_null() {
+            return Err(ffi_error(err));
+        }
+        Ok(sizes)
     }
 
     /// The sequence number of the most recent transaction.
@@ -1087,24 +2085,320 @@ impl Rdict {
         Ok(self.get_db()?.latest_sequence_number())
     }
 
-    /// Returns a list of all table files with their level, start key and end key
+    /// Returns a list of all table files with their level, start key and end key,
+    /// followed by the blob files (see `Options.set_enable_blob_files`).
+    ///
+    /// Notes:
+    ///     The RocksDB C API has no metadata of blob files: they are listed
+    ///     from the DB directory with their `name` and `size` only, the other
+    ///     fields are `None`. Obsolete blob files are listed until RocksDB
+    ///     deletes them.
     fn live_files(&self, py: Python) -> PyResult<PyObject> {
         let db = self.get_db()?;
-        match db.live_files() {
-            Ok(lfs) => {
-                let result = PyList::empty(py);
-                for lf in lfs {
-                    result.append(display_live_file_dict(
-                        lf,
-                        py,
-                        &self.loads,
-                        self.opt_py.raw_mode,
-                    )?)?
+        let result = PyList::empty(py);
+        for lf in db.live_files().map_err(rocksdb_error)? {
+            result.append(display_live_file_dict(
+                lf,
+                py,
+                &self.codec,
+                self.opt_py.raw_mode,
+            )?)?
+        }
+        for (name, size) in blob_files(db.path())? {
+            let file_dict = PyDict::new(py);
+            file_dict.set_item("name", name)?;
+            file_dict.set_item("size", size)?;
+            for key in [
+                "level",
+                "start_key",
+                "end_key",
+                "num_entries",
+                "num_deletions",
+            ] {
+                file_dict.set_item(key, py.None())?;
+            }
+            result.append(file_dict)?;
+        }
+        Ok(result.to_object(py))
+    }
+
+    /// Metadata of the current column family, level by level
+    /// (the shape of the LSM tree).
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         meta = db.column_family_metadata()
+    ///         for level in meta["levels"]:
+    ///             print(level["level"], level["file_count"], level["size"])
+    ///
+    /// Returns:
+    ///     a dict with the `name`, total `size` and `file_count` of the
+    ///     column family, its `blob_file_count` and `blob_file_size`,
+    ///     and a list of `levels`. Each level is a dict with its `level`,
+    ///     `size`, `file_count` and `files`, the list of its SST files:
+    ///     dicts with `name`, `size`, `smallest_key`, `largest_key`,
+    ///     `num_entries` and `num_deletions`.
+    fn column_family_metadata(&self, py: Python) -> PyResult<PyObject> {
+        let db = self.get_db()?;
+        let cf = self.current_cf()?;
+        let mut live_files: HashMap<String, LiveFile> = db
+            .live_files()
+            .map_err(rocksdb_error)?
+            .into_iter()
+            .map(|lf| (lf.name.trim_start_matches('/').to_string(), lf))
+            .collect();
+        let (size, file_count, levels) = column_family_levels(db, &cf);
+        let raw_mode = self.opt_py.raw_mode;
+        let levels_list = PyList::empty(py);
+        for level in levels {
+            let files = PyList::empty(py);
+            for file in level.files {
+                let live_file = live_files.remove(file.name.trim_start_matches('/'));
+                let file_dict = PyDict::new(py);
+                file_dict.set_item("name", file.name)?;
+                file_dict.set_item("size", file.size)?;
+                file_dict.set_item(
+                    "smallest_key",
+                    decode_file_key(py, &file.smallest_key, &self.codec, raw_mode)?,
+                )?;
+                file_dict.set_item(
+                    "largest_key",
+                    decode_file_key(py, &file.largest_key, &self.codec, raw_mode)?,
+                )?;
+                file_dict.set_item("num_entries", live_file.as_ref().map(|lf| lf.num_entries))?;
+                file_dict.set_item(
+                    "num_deletions",
+                    live_file.as_ref().map(|lf| lf.num_deletions),
+                )?;
+                files.append(file_dict)?;
+            }
+            let level_dict = PyDict::new(py);
+            level_dict.set_item("level", level.level)?;
+            level_dict.set_item("size", level.size)?;
+            level_dict.set_item("file_count", files.len())?;
+            level_dict.set_item("files", files)?;
+            levels_list.append(level_dict)?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("name", self.column_family_name())?;
+        result.set_item("size", size)?;
+        result.set_item("file_count", file_count)?;
+        result.set_item(
+            "blob_file_count",
+            self.property_int_value("rocksdb.num-blob-files")?,
+        )?;
+        result.set_item(
+            "blob_file_size",
+            self.property_int_value("rocksdb.total-blob-file-size")?,
+        )?;
+        result.set_item("levels", levels_list)?;
+        Ok(result.to_object(py))
+    }
+
+    /// Properties of the SST files of the current column family,
+    /// limited to the fields of `live_files`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         for name, props in db.get_properties_of_all_tables().items():
+    ///             print(name, props["level"], props["num_entries"])
+    ///
+    /// Returns:
+    ///     a dict mapping the file names to dicts with the `name`, `size`,
+    ///     `level`, `start_key`, `end_key`, `num_entries` and `num_deletions`
+    ///     of each file (see `live_files`).
+    ///
+    /// Notes:
+    ///     The C API of RocksDB does not expose the `TableProperties` of
+    ///     individual files, so the raw key and value sizes, the compression
+    ///     ratio and the filter policy of each file are not available: they
+    ///     can only be read aggregated over the files of the column family
+    ///     or of a level, see `aggregated_table_properties`.
+    ///     For the same reason, the properties written by table properties
+    ///     collectors cannot be read, and only the built-in collector of
+    ///     `Options.add_compact_on_deletion_collector_factory` can be
+    ///     registered: custom collectors are not supported.
+    fn get_properties_of_all_tables(&self, py: Python) -> PyResult<PyObject> {
+        self.tables_properties(py, None)
+    }
+
+    /// Properties of the SST files of the current column family
+    /// overlapping with at least one of the given ranges,
+    /// limited to the fields of `live_files`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         props = db.get_properties_of_tables_in_range([(0, 100), (1000, None)])
+    ///
+    /// Args:
+    ///     ranges: a list of `(start, end)` key ranges, `end` excluded.
+    ///         `None` stands for an unbounded side.
+    ///
+    /// Returns:
+    ///     a dict mapping the file names to their properties,
+    ///     as in `get_properties_of_all_tables`, with the same limitations.
+    fn get_properties_of_tables_in_range(
+        &self,
+        ranges: Vec<(&PyAny, &PyAny)>,
+        py: Python,
+    ) -> PyResult<PyObject> {
+        let ranges = ranges
+            .into_iter()
+            .map(|(start, end)| {
+                Ok((
+                    self.encode_bound(Some(start))?,
+                    self.encode_bound(Some(end))?,
+                ))
+            })
+            .collect::<PyResult<Vec<KeyRange>>>()?;
+        self.tables_properties(py, Some(&ranges))
+    }
+
+    /// Table properties of the current column family, aggregated over
+    /// all its SST files, or over the files of one level.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         props = db.aggregated_table_properties()
+    ///         print(props["# entries"], props["raw key size"], props["compression ratio"])
+    ///
+    /// Args:
+    ///     level: aggregate the files of this level only, all files if `None`.
+    ///
+    /// Returns:
+    ///     a dict of the properties as named by RocksDB (`"# entries"`,
+    ///     `"raw key size"`, `"raw value size"`, `"data block size"`,
+    ///     `"filter block size"`, ...) with int or float values,
+    ///     plus the `"compression ratio"`: raw size over data block size.
+    #[pyo3(signature = (level = None))]
+    fn aggregated_table_properties(&self, level: Option<usize>, py: Python) -> PyResult<PyObject> {
+        let name = match level {
+            None => "rocksdb.aggregated-table-properties".to_string(),
+            Some(level) => format!("rocksdb.aggregated-table-properties-at-level{level}"),
+        };
+        let result = PyDict::new(py);
+        let value = match self.property_value(&name)? {
+            None => return Ok(result.to_object(py)),
+            Some(value) => value,
+        };
+        let mut raw_size = 0;
+        let mut data_size = 0;
+        for prop in value.split(';') {
+            let (key, value) = match prop.split_once('=') {
+                None => continue,
+                Some((key, value)) => (key.trim(), value.trim()),
+            };
+            if let Ok(int) = value.parse::<u64>() {
+                match key {
+                    "raw key size" | "raw value size" => raw_size += int,
+                    "data block size" => data_size = int,
+                    _ => {}
                 }
-                Ok(result.to_object(py))
+                result.set_item(key, int)?;
+            } else if let Ok(float) = value.parse::<f64>() {
+                result.set_item(key, float)?;
+            } else {
+                result.set_item(key, value)?;
             }
-            Err(e) => Err(PyException::new_err(e.to_string())),
         }
+        if data_size > 0 {
+            result.set_item("compression ratio", raw_size as f64 / data_size as f64)?;
+        }
+        Ok(result.to_object(py))
+    }
+
+    /// Statistics of the blob files of the current column family
+    /// (see `Options.set_enable_blob_files`).
+    ///
+    /// Returns:
+    ///     a dict with the `num_blob_files`, the `total_blob_file_size`
+    ///     (all versions), the `live_blob_file_size` (current version),
+    ///     and the `live_blob_file_garbage_size`.
+    fn blob_stats(&self, py: Python) -> PyResult<PyObject> {
+        let result = PyDict::new(py);
+        for (key, property) in [
+            ("num_blob_files", "rocksdb.num-blob-files"),
+            ("total_blob_file_size", "rocksdb.total-blob-file-size"),
+            ("live_blob_file_size", "rocksdb.live-blob-file-size"),
+            (
+                "live_blob_file_garbage_size",
+                "rocksdb.live-blob-file-garbage-size",
+            ),
+        ] {
+            result.set_item(key, self.property_int_value(property)?)?;
+        }
+        Ok(result.to_object(py))
+    }
+
+    /// What the block cache of the current column family holds,
+    /// by role of the cache entries (the `rocksdb.block-cache-entry-stats`
+    /// property).
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         stats = db.block_cache_entry_stats()
+    ///         print(stats["usage"], stats["capacity"])
+    ///         for role, entries in stats["roles"].items():
+    ///             print(role, entries["count"], entries["size"], entries["portion"])
+    ///
+    /// Returns:
+    ///     a dict with the `capacity` and `usage` of the cache, and `roles`:
+    ///     a dict mapping the roles (`DataBlock`, `IndexBlock`, `FilterBlock`,
+    ///     `WriteBuffer`, ...) of the entries present in the cache to their
+    ///     `count`, `size` and `portion` (of the capacity, in percent).
+    ///
+    /// Notes:
+    ///     Sizes are reported by RocksDB with two decimals
+    ///     in KB, MB, GB or TB, so they are approximate.
+    ///     The stats are collected by scanning the cache,
+    ///     at most once a few minutes (older results are reused).
+    fn block_cache_entry_stats(&self, py: Python) -> PyResult<PyObject> {
+        let result = PyDict::new(py);
+        let value = match self.property_value("rocksdb.block-cache-entry-stats")? {
+            None => return Ok(result.to_object(py)),
+            Some(value) => value,
+        };
+        let roles = PyDict::new(py);
+        for line in value.lines() {
+            if let Some(stats) = line.strip_prefix("Block cache entry stats(count,size,portion):") {
+                // " DataBlock(3,12.00 KB,0.15%)"
+                for entry in stats.split(')') {
+                    let (role, entry) = match entry.trim().split_once('(') {
+                        None => continue,
+                        Some(split) => split,
+                    };
+                    let fields: Vec<&str> = entry.split(',').collect();
+                    if let [count, size, portion] = fields[..] {
+                        let entry_dict = PyDict::new(py);
+                        entry_dict.set_item("count", count.parse::<u64>().ok())?;
+                        entry_dict.set_item("size", human_bytes(size))?;
+                        entry_dict.set_item(
+                            "portion",
+                            portion.trim_end_matches('%').parse::<f64>().ok(),
+                        )?;
+                        roles.set_item(role, entry_dict)?;
+                    }
+                }
+            } else {
+                // "Block cache LRUCache@0x5583 capacity: 8.00 MB seed: ... usage: 0.09 KB ..."
+                let words: Vec<&str> = line.split_whitespace().collect();
+                for key in ["capacity", "usage"] {
+                    let label = format!("{key}:");
+                    if let Some(i) = words.iter().position(|w| *w == label) {
+                        if let (Some(value), Some(unit)) = (words.get(i + 1), words.get(i + 2)) {
+                            result.set_item(key, human_bytes(&format!("{value} {unit}")))?;
+                        }
+                    }
+                }
+            }
+        }
+        result.set_item("roles", roles)?;
+        Ok(result.to_object(py))
     }
 
     /// Delete the database.
@@ -1116,7 +2410,7 @@ impl Rdict {
     #[pyo3(signature = (path, options = OptionsPy::new(false)))]
     fn destroy(path: &str, options: OptionsPy) -> PyResult<()> {
         fs::remove_file(config_file(path)).ok();
-        DB::destroy(&options.inner_opt, path).map_err(|e| PyException::new_err(e.to_string()))
+        DB::destroy(&options.inner_opt, path).map_err(rocksdb_error)
     }
 
     /// Repair the database.
@@ -1126,31 +2420,122 @@ impl Rdict {
     ///     options (rocksdict.Options): Rocksdb options object
     #[staticmethod]
     #[pyo3(signature = (path, options = OptionsPy::new(false)))]
-    fn repair(path: &str, options: OptionsPy) -> PyResult<()> {
-        DB::repair(&options.inner_opt, path).map_err(|e| PyException::new_err(e.to_string()))
+    fn repair(path: &str, mut options: OptionsPy) -> PyResult<()> {
+        if !options.raw_mode && has_namespaced_comparator(path)? {
+            options.set_enable_namespaces(true);
+        }
+        DB::repair(&options.inner_opt, path).map_err(rocksdb_error)
     }
 
     #[staticmethod]
     #[pyo3(signature = (path, options = OptionsPy::new(false)))]
     fn list_cf(path: &str, options: OptionsPy) -> PyResult<Vec<String>> {
-        DB::list_cf(&options.inner_opt, path).map_err(|e| PyException::new_err(e.to_string()))
+        DB::list_cf(&options.inner_opt, path).map_err(rocksdb_error)
+    }
+
+    /// Names of the column families currently open.
+    ///
+    /// Unlike `Rdict.list_cf`, which lists the column families
+    /// stored on disk, this does not include the column families
+    /// that were not opened (e.g. in read-only mode).
+    fn column_families(&self) -> PyResult<Vec<String>> {
+        let db = self.get_db()?;
+        let names = DB::list_cf(&rocksdb::Options::default(), db.path()).map_err(rocksdb_error)?;
+        Ok(names
+            .into_iter()
+            .filter(|name| unsafe { db.cf_handle_unbounded(name) }.is_some())
+            .collect())
+    }
+}
+
+/// Whether the database at `path` was created with namespaces enabled.
+fn has_namespaced_comparator(path: &str) -> PyResult<bool> {
+    Ok(
+        PersistedOptions::load_latest(path)?.map_or(false, |persisted| {
+            persisted.cfs.values().any(|cf| {
+                cf.get("comparator").map(|c| c.as_str()) == Some(NAMESPACED_ROCKSDICT_COMPARATOR)
+            })
+        }),
+    )
+}
+
+/// The options of every existing column family of the database at `path`:
+/// from `column_families` if present, otherwise from the OPTIONS file,
+/// otherwise the default options with the prefix extractor and codec
+/// persisted in the rocksdict config.
+fn all_column_families(
+    path: &str,
+    options: &OptionsPy,
+    column_families: Option<HashMap<String, OptionsPy>>,
+) -> PyResult<HashMap<String, OptionsPy>> {
+    let mut column_families = column_families.unwrap_or_default();
+    let names = match DB::list_cf(&options.inner_opt, path) {
+        Ok(names) => names,
+        // new database
+        Err(_) => return Ok(column_families),
+    };
+    let mut loaded = OptionsPy::load_latest_inner(
+        path,
+        EnvPy::default()?,
+        true,
+        CachePy::new_lru_cache(DEFAULT_LRU_CACHE_SIZE),
+    )
+    .map(|(_, cfs)| cfs)
+    .unwrap_or_default();
+    let config = RocksDictConfig::load(config_file(path)).ok();
+    for name in names {
+        if name == DEFAULT_COLUMN_FAMILY_NAME || column_families.contains_key(&name) {
+            continue;
+        }
+        let opt = match loaded.remove(&name) {
+            Some(opt) => opt,
+            None => OptionsPy::compose_options_py(
+                rocksdb::Options::default(),
+                options.raw_mode,
+                config
+                    .as_ref()
+                    .and_then(|c| c.prefix_extractors.get(&name).cloned()),
+                config.as_ref().and_then(|c| c.codecs.get(&name)),
+                config
+                    .as_ref()
+                    .and_then(|c| c.value_compressions.get(&name).cloned()),
+            )?,
+        };
+        column_families.insert(name, opt);
     }
+    Ok(column_families)
+}
+
+/// parse a size printed by RocksDB, like `12.00 KB`
+fn human_bytes(size: &str) -> Option<u64> {
+    let (value, unit) = size.trim().split_once(' ')?;
+    let exponent = match unit {
+        "KB" => 1,
+        "MB" => 2,
+        "GB" => 3,
+        "TB" => 4,
+        _ => return None,
+    };
+    Some((value.parse::<f64>().ok()? * 1024f64.powi(exponent)) as u64)
 }
 
+/// an encoded `(start, end)` key range, `None` for unbounded sides
+type KeyRange = (Option<Box<[u8]>>, Option<Box<[u8]>>);
+
 fn display_live_file_dict(
     lf: LiveFile,
     py: Python,
-    pickle_loads: &PyObject,
+    codec: &CodecPy,
     raw_mode: bool,
 ) -> PyResult<PyObject> {
     let result = PyDict::new(py);
     let start_key = match lf.start_key {
         None => py.None(),
-        Some(k) => decode_value(py, &k, pickle_loads, raw_mode)?,
+        Some(k) => decode_file_key(py, &k, codec, raw_mode)?,
     };
     let end_key = match lf.end_key {
         None => py.None(),
-        Some(k) => decode_value(py, &k, pickle_loads, raw_mode)?,
+        Some(k) => decode_file_key(py, &k, codec, raw_mode)?,
     };
     result.set_item("name", lf.name)?;
     result.set_item("size", lf.size)?;
@@ -1162,28 +2547,180 @@ fn display_live_file_dict(
     Ok(result.to_object(py))
 }
 
+/// the `(name, size)` of the blob files in the directory of a database,
+/// named like the table files of `DB::live_files` (`/000012.blob`).
+fn blob_files(path: &Path) -> PyResult<Vec<(String, u64)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".blob") {
+            files.push((format!("/{name}"), entry.metadata()?.len()));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// decode the smallest or largest key of a file, which
+/// may belong to a namespace: namespaced keys are returned as bytes.
+fn decode_file_key(py: Python, key: &[u8], codec: &CodecPy, raw_mode: bool) -> PyResult<PyObject> {
+    if !raw_mode && key.first() == Some(&NAMESPACE_TAG) {
+        Ok(PyBytes::new(py, key).to_object(py))
+    } else {
+        decode_value(py, key, codec, raw_mode)
+    }
+}
+
+/// Options of a column family in `Rdict(column_families=...)`:
+/// `Options`, or `(Options, ttl_seconds)` for entries that expire.
+#[derive(FromPyObject)]
+pub(crate) enum ColumnFamilyOptions {
+    Options(OptionsPy),
+    WithTtl(OptionsPy, Option<u64>),
+}
+
+impl ColumnFamilyOptions {
+    /// the options, and the ttl if it is set (`Some(None)` removes it)
+    fn into_options(self) -> (OptionsPy, Option<Option<u64>>) {
+        match self {
+            ColumnFamilyOptions::Options(opt) => (opt, None),
+            ColumnFamilyOptions::WithTtl(opt, ttl) => (opt, Some(ttl)),
+        }
+    }
+}
+
+/// descriptor of a column family, with its per-key `ttl` if it has one
+fn cf_descriptor(
+    name: &str,
+    options: rocksdb::Options,
+    ttls: &HashMap<String, u64>,
+) -> ColumnFamilyDescriptor {
+    match ttls.get(name) {
+        Some(ttl) => ColumnFamilyDescriptor::new_with_ttl(
+            name,
+            options,
+            ColumnFamilyTtl::Duration(Duration::from_secs(*ttl)),
+        ),
+        None => ColumnFamilyDescriptor::new(name, options),
+    }
+}
+
+/// name of a column family handle
+fn cf_name(cf: &Arc<UnboundColumnFamily>) -> String {
+    unsafe {
+        let mut len: libc::size_t = 0;
+        let name = librocksdb_sys::rocksdb_column_family_handle_get_name(cf.inner(), &mut len);
+        let result = String::from_utf8_lossy(std::slice::from_raw_parts(name as *const u8, len))
+            .into_owned();
+        librocksdb_sys::rocksdb_free(name as *mut libc::c_void);
+        result
+    }
+}
+
+struct SstFileMetadata {
+    name: String,
+    size: u64,
+    smallest_key: Vec<u8>,
+    largest_key: Vec<u8>,
+}
+
+struct LevelMetadata {
+    level: i32,
+    size: u64,
+    files: Vec<SstFileMetadata>,
+}
+
+/// read the metadata of a column family through the C API,
+/// returning `(size, file_count, levels)`
+fn column_family_levels(
+    db: &DB,
+    cf: &Arc<UnboundColumnFamily>,
+) -> (u64, usize, Vec<LevelMetadata>) {
+    unsafe fn take_bytes(ptr: *mut c_char, len: size_t) -> Vec<u8> {
+        let bytes = std::slice::from_raw_parts(ptr as *const u8, len).to_vec();
+        librocksdb_sys::rocksdb_free(ptr as *mut libc::c_void);
+        bytes
+    }
+    unsafe {
+        let meta = librocksdb_sys::rocksdb_get_column_family_metadata_cf(db.inner(), cf.inner());
+        let size = librocksdb_sys::rocksdb_column_family_metadata_get_size(meta);
+        let file_count = librocksdb_sys::rocksdb_column_family_metadata_get_file_count(meta);
+        let level_count = librocksdb_sys::rocksdb_column_family_metadata_get_level_count(meta);
+        let mut levels = Vec::with_capacity(level_count);
+        for i in 0..level_count {
+            let level_meta =
+                librocksdb_sys::rocksdb_column_family_metadata_get_level_metadata(meta, i);
+            let level_file_count =
+                librocksdb_sys::rocksdb_level_metadata_get_file_count(level_meta);
+            let mut files = Vec::with_capacity(level_file_count);
+            for j in 0..level_file_count {
+                let file_meta =
+                    librocksdb_sys::rocksdb_level_metadata_get_sst_file_metadata(level_meta, j);
+                let name =
+                    librocksdb_sys::rocksdb_sst_file_metadata_get_relative_filename(file_meta);
+                let name = take_bytes(name, libc::strlen(name));
+                let mut len: size_t = 0;
+                let smallest =
+                    librocksdb_sys::rocksdb_sst_file_metadata_get_smallestkey(file_meta, &mut len);
+                let smallest_key = take_bytes(smallest, len);
+                let largest =
+                    librocksdb_sys::rocksdb_sst_file_metadata_get_largestkey(file_meta, &mut len);
+                let largest_key = take_bytes(largest, len);
+                files.push(SstFileMetadata {
+                    name: String::from_utf8_lossy(&name).into_owned(),
+                    size: librocksdb_sys::rocksdb_sst_file_metadata_get_size(file_meta),
+                    smallest_key,
+                    largest_key,
+                });
+                librocksdb_sys::rocksdb_sst_file_metadata_destroy(file_meta);
+            }
+            levels.push(LevelMetadata {
+                level: librocksdb_sys::rocksdb_level_metadata_get_level(level_meta),
+                size: librocksdb_sys::rocksdb_level_metadata_get_size(level_meta),
+                files,
+            });
+            librocksdb_sys::rocksdb_level_metadata_destroy(level_meta);
+        }
+        librocksdb_sys::rocksdb_column_family_metadata_destroy(meta);
+        (size, file_count, levels)
+    }
+}
+
+/// codec names stored in the rocksdict config file
+fn codec_names(codecs: &HashMap<String, CodecPy>) -> HashMap<String, String> {
+    codecs
+        .iter()
+        .map(|(cf, codec)| (cf.clone(), codec.name.clone()))
+        .collect()
+}
+
+/// value compressions stored in the rocksdict config file
+fn value_compressions(codecs: &HashMap<String, CodecPy>) -> HashMap<String, ValueCompressionType> {
+    codecs
+        .iter()
+        .filter_map(|(cf, codec)| Some((cf.clone(), codec.compression.clone()?)))
+        .collect()
+}
+
 fn get_batch_inner<'a>(
     db: &DB,
-    key_list: &'a PyList,
+    keys: &[Cow<[u8]>],
     py: Python<'a>,
     read_opt: &ReadOptions,
-    loads: &PyObject,
+    codec: &CodecPy,
     cf: &Arc<UnboundColumnFamily>,
     raw_mode: bool,
 ) -> PyResult<&'a PyList> {
-    let mut keys: Vec<Cow<[u8]>> = Vec::with_capacity(key_list.len());
-    for key in key_list {
-        keys.push(encode_key(key, raw_mode)?);
-    }
-    let values = db.batched_multi_get_cf_opt(cf, &keys, false, read_opt);
+    let values = db.batched_multi_get_cf_opt(cf, keys, false, read_opt);
     let result = PyList::empty(py);
     for v in values {
         match v {
             Ok(value) => match value {
                 None => result.append(py.None())?,
-                Some(slice) => result.append(decode_value(py, slice.as_ref(), loads, raw_mode)?)?,
+                Some(slice) => result.append(decode_value(py, slice.as_ref(), codec, raw_mode)?)?,
             },
-            Err(e) => return Err(PyException::new_err(e.to_string())),
+            Err(e) => return Err(rocksdb_error(e)),
         }
     }
     Ok(result)
@@ -1218,10 +2755,31 @@ pub(crate) struct ColumnFamilyPy {
     pub(crate) cf: Arc<UnboundColumnFamily>,
     // must keep db alive
     db: DbReferenceHolder,
+    // codec configured for this column family
+    pub(crate) codec: Option<CodecPy>,
 }
 
 unsafe impl Send for ColumnFamilyPy {}
 
+#[pymethods]
+impl ColumnFamilyPy {
+    /// Name of the column family.
+    #[getter]
+    fn name(&self) -> String {
+        cf_name(&self.cf)
+    }
+
+    /// ID of the column family, unique within the database.
+    #[getter]
+    fn id(&self) -> u32 {
+        unsafe { librocksdb_sys::rocksdb_column_family_handle_get_id(self.cf.inner()) }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ColumnFamily(name={:?}, id={})", self.name(), self.id())
+    }
+}
+
 #[pymethods]
 impl AccessType {
     /// Define DB Access Types.
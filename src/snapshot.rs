@@ -1,9 +1,13 @@
+use crate::codec::CodecPy;
 use crate::db_reference::{DbReference, DbReferenceHolder};
 use crate::encoder::{decode_value, encode_key};
-use crate::exceptions::DbClosedError;
+use crate::exceptions::{rocksdb_error, DbClosedError};
+use crate::namespace::KeyNamespace;
+use crate::range::{slice_bounds, RdictRange};
 use crate::{Rdict, RdictItems, RdictIter, RdictKeys, RdictValues, ReadOptionsPy};
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
+use pyo3::types::PySlice;
 use rocksdb::{ReadOptions, UnboundColumnFamily};
 use std::sync::Arc;
 
@@ -40,11 +44,13 @@ use std::sync::Arc;
 pub struct Snapshot {
     pub(crate) inner: *const librocksdb_sys::rocksdb_snapshot_t,
     pub(crate) column_family: Option<Arc<UnboundColumnFamily>>,
-    pub(crate) pickle_loads: PyObject,
+    pub(crate) codec: CodecPy,
     pub(crate) read_opt: ReadOptions,
     // decrease db Rc last
     pub(crate) db: DbReferenceHolder,
     pub(crate) raw_mode: bool,
+    pub(crate) namespaces: bool,
+    pub(crate) namespace: Option<KeyNamespace>,
 }
 
 #[pymethods]
@@ -55,23 +61,12 @@ impl Snapshot {
     /// Args:
     ///     read_opt: ReadOptions, must have the same `raw_mode` argument.
     #[pyo3(signature = (read_opt = None))]
-    fn iter(&self, read_opt: Option<&ReadOptionsPy>, py: Python) -> PyResult<RdictIter> {
+    fn iter(slf: PyRef<Self>, read_opt: Option<&ReadOptionsPy>, py: Python) -> PyResult<RdictIter> {
         let read_opt: ReadOptionsPy = match read_opt {
             None => ReadOptionsPy::default(py)?,
             Some(opt) => opt.clone(),
         };
-        let opt_pointer = read_opt.to_read_opt(self.raw_mode, py)?;
-        unsafe {
-            set_snapshot(opt_pointer.0, self.inner);
-        }
-        RdictIter::new(
-            &self.db,
-            &self.column_family,
-            read_opt,
-            &self.pickle_loads,
-            self.raw_mode,
-            py,
-        )
+        Self::iter_with(slf, read_opt, py)
     }
 
     /// Iterate through all keys and values pairs.
@@ -84,13 +79,13 @@ impl Snapshot {
     ///     read_opt: ReadOptions, must have the same `raw_mode` argument.
     #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
     fn items(
-        &self,
+        slf: PyRef<Self>,
         backwards: bool,
         from_key: Option<&PyAny>,
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictItems> {
-        RdictItems::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictItems::new(Self::iter(slf, read_opt, py)?, backwards, from_key)
     }
 
     /// Iterate through all keys.
@@ -103,13 +98,13 @@ impl Snapshot {
     ///     read_opt: ReadOptions, must have the same `raw_mode` argument.
     #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
     fn keys(
-        &self,
+        slf: PyRef<Self>,
         backwards: bool,
         from_key: Option<&PyAny>,
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictKeys> {
-        RdictKeys::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictKeys::new(Self::iter(slf, read_opt, py)?, backwards, from_key)
     }
 
     /// Iterate through all values.
@@ -122,19 +117,81 @@ impl Snapshot {
     ///     read_opt: ReadOptions, must have the same `raw_mode` argument.
     #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
     fn values(
-        &self,
+        slf: PyRef<Self>,
         backwards: bool,
         from_key: Option<&PyAny>,
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictValues> {
-        RdictValues::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictValues::new(Self::iter(slf, read_opt, py)?, backwards, from_key)
+    }
+
+    /// Set the codec used to deserialize values.
+    ///
+    /// Args:
+    ///     codec (Codec): the codec of the column family.
+    fn set_codec(&mut self, codec: CodecPy) {
+        self.codec = codec
+    }
+
+    /// read from snapshot.
+    ///
+    /// A slice (e.g. `snapshot[10:20]`) returns a lazily evaluated,
+    /// read-only `RdictRange` view of the keys in `[start, stop)`.
+    fn __getitem__(slf: PyRef<Self>, key: &PyAny, py: Python) -> PyResult<PyObject> {
+        if let Ok(slice) = key.downcast::<PySlice>() {
+            let (start, end) = slice_bounds(slice)?;
+            let view = RdictRange {
+                db: slf.db.clone(),
+                column_family: slf.column_family.clone(),
+                codec: slf.codec.clone(),
+                raw_mode: slf.raw_mode,
+                namespaces: slf.namespaces,
+                read_opt: ReadOptionsPy::default(py)?,
+                start,
+                end,
+                namespace: slf.namespace.clone(),
+                snapshot: Some(slf.into()),
+                write: None,
+            };
+            return Ok(Py::new(py, view)?.into_py(py));
+        }
+        slf.get(key, py)
+    }
+}
+
+impl Snapshot {
+    /// Creates an iterator over this snapshot.
+    pub(crate) fn iter_with(
+        slf: PyRef<Self>,
+        read_opt: ReadOptionsPy,
+        py: Python,
+    ) -> PyResult<RdictIter> {
+        let db = slf.db.clone();
+        let column_family = slf.column_family.clone();
+        let codec = slf.codec.clone();
+        let raw_mode = slf.raw_mode;
+        let namespaces = slf.namespaces;
+        let namespace = slf.namespace.clone();
+        RdictIter::new(
+            &db,
+            &column_family,
+            read_opt,
+            &codec,
+            raw_mode,
+            namespaces,
+            namespace,
+            Some(slf.into()),
+            py,
+        )
     }
 
-    /// read from snapshot
-    fn __getitem__(&self, key: &PyAny, py: Python) -> PyResult<PyObject> {
+    fn get(&self, key: &PyAny, py: Python) -> PyResult<PyObject> {
         let db = self.get_db();
-        let key = encode_key(key, self.raw_mode)?;
+        let mut key = encode_key(key, self.raw_mode)?;
+        if let Some(ns) = &self.namespace {
+            key = ns.key(&key).into();
+        }
         let value_result = if let Some(cf) = &self.column_family {
             db.get_pinned_cf_opt(cf, &key[..], &self.read_opt)
         } else {
@@ -143,9 +200,9 @@ impl Snapshot {
         match value_result {
             Ok(value) => match value {
                 None => Err(PyException::new_err("key not found")),
-                Some(slice) => decode_value(py, slice.as_ref(), &self.pickle_loads, self.raw_mode),
+                Some(slice) => decode_value(py, slice.as_ref(), &self.codec, self.raw_mode),
             },
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(rocksdb_error(e)),
         }
     }
 }
@@ -167,10 +224,12 @@ impl Snapshot {
         Ok(Snapshot {
             inner: snapshot,
             column_family: rdict.column_family.clone(),
-            pickle_loads: rdict.loads.clone(),
+            codec: rdict.codec.clone(),
             read_opt: r_opt,
             db: rdict.db.clone(),
             raw_mode: rdict.opt_py.raw_mode,
+            namespaces: rdict.opt_py.namespaces,
+            namespace: rdict.namespace.clone(),
         })
     }
 
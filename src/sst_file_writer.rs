@@ -1,9 +1,12 @@
-use crate::encoder::{encode_key, encode_value};
-use crate::util::{error_message, to_cpath};
+use crate::codec::CodecPy;
+use crate::encoder::{decode_value, encode_key, encode_value};
+use crate::exceptions::ffi_error;
+use crate::util::to_cpath;
 use crate::OptionsPy;
 use libc::{self, c_char, size_t};
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use pyo3::PyResult;
 use rocksdb::Options;
 use std::ffi::CString;
@@ -23,7 +26,7 @@ macro_rules! ffi_try_impl {
         let mut err: *mut ::libc::c_char = ::std::ptr::null_mut();
         let result = $($function)::*($($arg,)* &mut err);
         if !err.is_null() {
-            return Err(PyException::new_err(error_message(err)));
+            return Err(ffi_error(err));
         }
         result
     }};
@@ -39,8 +42,34 @@ macro_rules! ffi_try_impl {
 pub struct SstFileWriterPy {
     pub(crate) inner: *mut librocksdb_sys::rocksdb_sstfilewriter_t,
     opts: Options,
-    dumps: PyObject,
+    codec: CodecPy,
     raw_mode: bool,
+    /// what was written to the file currently open
+    current: FileInfo,
+    /// the last file finished
+    finished: Option<FileInfo>,
+}
+
+/// The summary of a file written, for `file_info`.
+#[derive(Default)]
+struct FileInfo {
+    path: String,
+    smallest_key: Option<Box<[u8]>>,
+    largest_key: Option<Box<[u8]>>,
+    num_entries: u64,
+    num_range_del_entries: u64,
+    file_size: u64,
+}
+
+impl FileInfo {
+    /// record a point entry (put, merge or delete)
+    fn add(&mut self, key: &[u8]) {
+        if self.smallest_key.is_none() {
+            self.smallest_key = Some(key.into());
+        }
+        self.largest_key = Some(key.into());
+        self.num_entries += 1;
+    }
 }
 
 unsafe impl Send for SstFileWriterPy {}
@@ -71,31 +100,48 @@ impl SstFileWriterPy {
     ///
     /// Args:
     ///     options: this options must have the same `raw_mode` as the Rdict DB.
+    ///         The codec set by `Options.set_codec` is used to serialize values,
+    ///         and `Options.set_value_compression` to compress them.
     #[new]
     #[pyo3(signature = (options = OptionsPy::new(false)))]
     fn create(options: OptionsPy, py: Python) -> PyResult<Self> {
         let env_options = EnvOptions::default();
         let raw_mode = options.raw_mode;
+        let codec = match options.value_codec(py)? {
+            Some(codec) => codec,
+            None => CodecPy::pickle(py)?,
+        };
         let options = &options.inner_opt;
         let writer = Self::create_raw(options, &env_options);
-        let pickle = PyModule::import(py, "pickle")?.to_object(py);
-        let pickle_dumps = pickle.getattr(py, "dumps")?;
 
         Ok(Self {
             inner: writer,
             opts: options.clone(),
-            dumps: pickle_dumps,
+            codec,
             raw_mode,
+            current: FileInfo::default(),
+            finished: None,
         })
     }
 
     /// set custom dumps function
     fn set_dumps(&mut self, dumps: PyObject) {
-        self.dumps = dumps
+        self.codec.dumps = Some(dumps)
+    }
+
+    /// Set the codec used to serialize values.
+    ///
+    /// Args:
+    ///     codec (Codec): should be the same as the codec of the target column family.
+    fn set_codec(&mut self, codec: CodecPy) {
+        self.codec = CodecPy {
+            compression: self.codec.compression.take(),
+            ..codec
+        }
     }
 
     /// Prepare SstFileWriter to write into file located at "file_path".
-    fn open(&self, path: &str) -> PyResult<()> {
+    fn open(&mut self, path: &str) -> PyResult<()> {
         let cpath = to_cpath(path)?;
         self.open_raw(&cpath)
     }
@@ -114,7 +160,7 @@ impl SstFileWriterPy {
     /// REQUIRES: key is after any previously added key according to comparator.
     fn __setitem__(&mut self, key: &PyAny, value: &PyAny) -> PyResult<()> {
         let key = encode_key(key, self.raw_mode)?;
-        let value = encode_value(value, &self.dumps, self.raw_mode)?;
+        let value = encode_value(value, &self.codec, self.raw_mode)?;
         self.setitem_raw(&key, &value)
     }
 
@@ -124,9 +170,107 @@ impl SstFileWriterPy {
         let key = encode_key(key, self.raw_mode)?;
         self.delitem_raw(&key)
     }
+
+    /// Adds key-value pairs to currently opened file, from a dict
+    /// or from an iterable of `(key, value)` pairs.
+    /// REQUIRES: keys are in increasing order according to comparator,
+    /// and after any previously added key.
+    fn put_many(&mut self, items: &PyAny) -> PyResult<()> {
+        let items = if items.is_instance_of::<PyDict>() {
+            items.call_method0("items")?
+        } else {
+            items
+        };
+        for item in items.iter()? {
+            let (key, value): (&PyAny, &PyAny) = item?.extract()?;
+            let key = encode_key(key, self.raw_mode)?;
+            let value = encode_value(value, &self.codec, self.raw_mode)?;
+            self.setitem_raw(&key, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a Merge key with value to currently opened file.
+    /// REQUIRES: key is after any previously added key according to comparator.
+    ///
+    /// Notes:
+    ///     Reading merged keys requires the column family the file
+    ///     is ingested into to have a merge operator.
+    fn merge(&mut self, key: &PyAny, value: &PyAny) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode)?;
+        let value = encode_value(value, &self.codec, self.raw_mode)?;
+        unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_sstfilewriter_merge(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+        }
+        self.current.add(&key);
+        Ok(())
+    }
+
+    /// Adds a range deletion tombstone of `[begin, end)` to currently opened file.
+    /// Unlike point entries, range deletions can be added in any order.
+    fn delete_range(&mut self, begin: &PyAny, end: &PyAny) -> PyResult<()> {
+        let begin = encode_key(begin, self.raw_mode)?;
+        let end = encode_key(end, self.raw_mode)?;
+        unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_sstfilewriter_delete_range(
+                self.inner,
+                begin.as_ptr() as *const c_char,
+                begin.len() as size_t,
+                end.as_ptr() as *const c_char,
+                end.len() as size_t,
+            ));
+        }
+        self.current.num_range_del_entries += 1;
+        Ok(())
+    }
+
+    /// Information about the last file finished.
+    ///
+    /// Returns:
+    ///     a dict with the `path`, `file_size`, `smallest_key` and
+    ///     `largest_key` (of the point entries, `None` if there is none),
+    ///     `num_entries` (puts, merges and deletions) and
+    ///     `num_range_del_entries` of the file.
+    fn file_info(&self, py: Python) -> PyResult<PyObject> {
+        let info = self
+            .finished
+            .as_ref()
+            .ok_or_else(|| PyException::new_err("no file finished"))?;
+        let decode = |key: &Option<Box<[u8]>>| match key {
+            None => Ok(py.None()),
+            Some(key) => decode_value(py, key, &self.codec, self.raw_mode),
+        };
+        let result = PyDict::new(py);
+        result.set_item("path", &info.path)?;
+        result.set_item("file_size", info.file_size)?;
+        result.set_item("smallest_key", decode(&info.smallest_key)?)?;
+        result.set_item("largest_key", decode(&info.largest_key)?)?;
+        result.set_item("num_entries", info.num_entries)?;
+        result.set_item("num_range_del_entries", info.num_range_del_entries)?;
+        Ok(result.to_object(py))
+    }
 }
 
 impl SstFileWriterPy {
+    /// a writer of already encoded keys and values
+    pub(crate) fn new_raw(opts: &Options, codec: CodecPy, raw_mode: bool) -> Self {
+        let env_options = EnvOptions::default();
+        Self {
+            inner: Self::create_raw(opts, &env_options),
+            opts: opts.clone(),
+            codec,
+            raw_mode,
+            current: FileInfo::default(),
+            finished: None,
+        }
+    }
+
     #[inline]
     fn create_raw(
         opts: &Options,
@@ -136,34 +280,40 @@ impl SstFileWriterPy {
     }
 
     #[inline]
-    fn open_raw(&self, cpath: &CString) -> PyResult<()> {
+    pub(crate) fn open_raw(&mut self, cpath: &CString) -> PyResult<()> {
         unsafe {
             ffi_try!(librocksdb_sys::rocksdb_sstfilewriter_open(
                 self.inner,
                 cpath.as_ptr() as *const _
             ));
-
-            Ok(())
         }
+        self.current = FileInfo {
+            path: cpath.to_string_lossy().into_owned(),
+            ..FileInfo::default()
+        };
+        Ok(())
     }
 
     #[inline]
-    fn finish_raw(&mut self) -> PyResult<()> {
+    pub(crate) fn finish_raw(&mut self) -> PyResult<()> {
         unsafe {
             ffi_try!(librocksdb_sys::rocksdb_sstfilewriter_finish(self.inner,));
-            Ok(())
         }
+        let mut info = std::mem::take(&mut self.current);
+        info.file_size = self.file_size_raw();
+        self.finished = Some(info);
+        Ok(())
     }
 
     #[inline]
-    fn file_size_raw(&self) -> u64 {
+    pub(crate) fn file_size_raw(&self) -> u64 {
         let mut file_size: u64 = 0;
         unsafe { librocksdb_sys::rocksdb_sstfilewriter_file_size(self.inner, &mut file_size) };
         file_size
     }
 
     #[inline]
-    fn setitem_raw(&mut self, key: &[u8], value: &[u8]) -> PyResult<()> {
+    pub(crate) fn setitem_raw(&mut self, key: &[u8], value: &[u8]) -> PyResult<()> {
         unsafe {
             ffi_try!(librocksdb_sys::rocksdb_sstfilewriter_put(
                 self.inner,
@@ -173,6 +323,7 @@ impl SstFileWriterPy {
                 value.len() as size_t,
             ));
         }
+        self.current.add(key);
         Ok(())
     }
 
@@ -185,6 +336,7 @@ impl SstFileWriterPy {
                 key.len() as size_t,
             ));
         }
+        self.current.add(key);
         Ok(())
     }
 }
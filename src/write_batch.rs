@@ -1,3 +1,4 @@
+use crate::codec::{CodecPy, ValueCompressionPy};
 use crate::encoder::{encode_key, encode_value};
 use crate::ColumnFamilyPy;
 use pyo3::exceptions::PyException;
@@ -16,7 +17,7 @@ use rocksdb::WriteBatch;
 pub(crate) struct WriteBatchPy {
     inner: Option<WriteBatch>,
     default_column_family: Option<ColumnFamilyPy>,
-    dumps: PyObject,
+    codec: CodecPy,
     pub(crate) raw_mode: bool,
 }
 
@@ -33,18 +34,38 @@ impl WriteBatchPy {
     #[new]
     #[pyo3(signature = (raw_mode = false))]
     pub fn default(py: Python, raw_mode: bool) -> PyResult<Self> {
-        let pickle = PyModule::import(py, "pickle")?.to_object(py);
         Ok(WriteBatchPy {
             inner: Some(WriteBatch::default()),
             default_column_family: None,
-            dumps: pickle.getattr(py, "dumps")?,
+            codec: CodecPy::pickle(py)?,
             raw_mode,
         })
     }
 
     /// change to a custom dumps function
     pub fn set_dumps(&mut self, dumps: PyObject) {
-        self.dumps = dumps
+        self.codec.dumps = Some(dumps)
+    }
+
+    /// Set the codec used to serialize values.
+    ///
+    /// Notes:
+    ///     Column family handles obtained from `Rdict.get_column_family_handle`
+    ///     carry the codec configured for that column family, which takes
+    ///     precedence over the codec of this batch.
+    ///
+    /// Args:
+    ///     codec (Codec): the codec, should be the same as the one of the Rdict.
+    pub fn set_codec(&mut self, codec: CodecPy) {
+        self.codec = CodecPy {
+            compression: self.codec.compression.take(),
+            ..codec
+        }
+    }
+
+    /// Set the value compression of this batch, `None` to disable it.
+    pub fn set_value_compression(&mut self, compression: Option<ValueCompressionPy>) {
+        self.codec.compression = compression.map(|c| c.0)
     }
 
     pub fn __len__(&self) -> PyResult<usize> {
@@ -54,7 +75,8 @@ impl WriteBatchPy {
     pub fn __setitem__(&mut self, key: &PyAny, value: &PyAny) -> PyResult<()> {
         if let Some(inner) = &mut self.inner {
             let key = encode_key(key, self.raw_mode)?;
-            let value = encode_value(value, &self.dumps, self.raw_mode)?;
+            let codec = codec_of(&self.default_column_family, &self.codec);
+            let value = encode_value(value, codec, self.raw_mode)?;
             match &self.default_column_family {
                 None => inner.put(key, value),
                 Some(cf) => inner.put_cf(&cf.cf, key, value),
@@ -148,7 +170,8 @@ impl WriteBatchPy {
     ) -> PyResult<()> {
         if let Some(inner) = &mut self.inner {
             let key = encode_key(key, self.raw_mode)?;
-            let value = encode_value(value, &self.dumps, self.raw_mode)?;
+            let codec = codec_of(&column_family, &self.codec);
+            let value = encode_value(value, codec, self.raw_mode)?;
             match column_family {
                 Some(cf) => inner.put_cf(&cf.cf, key, value),
                 None => inner.put(key, value),
@@ -241,3 +264,12 @@ impl WriteBatchPy {
         }
     }
 }
+
+/// the codec of a column family handle, or the codec of the batch
+#[inline]
+fn codec_of<'a>(column_family: &'a Option<ColumnFamilyPy>, codec: &'a CodecPy) -> &'a CodecPy {
+    column_family
+        .as_ref()
+        .and_then(|cf| cf.codec.as_ref())
+        .unwrap_or(codec)
+}
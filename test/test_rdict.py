@@ -8,11 +8,37 @@ from rocksdict import (
     SliceTransform,
     CuckooTableOptions,
     DbClosedError,
+    Codec,
+    WriteBatch,
+    BulkLoader,
+    SstFileWriter,
+    WriteBufferManager,
+    Cache,
+    BlockBasedOptions,
+    PinningTier,
+    DBCompactionPri,
+    DBCompressionType,
+    ValueCompression,
+    RocksDBError,
+    RocksIOError,
+    LockError,
+    InvalidArgumentError,
+    NotSupportedError,
 )
 from random import randint, random, getrandbits
 import os
+import shutil
 import sys
+import time
 from json import loads, dumps
+from datetime import date, datetime, timedelta, timezone
+from decimal import Decimal
+from uuid import UUID, uuid4
+
+try:
+    import numpy as np
+except ImportError:
+    np = None
 
 
 TEST_INT_RANGE_UPPER = 999999
@@ -454,6 +480,1104 @@ class TestString(unittest.TestCase):
         Rdict.destroy(cls.path, cls.opt)
 
 
+@unittest.skipIf(np is None, "numpy is not installed")
+class TestNumpyArray(unittest.TestCase):
+    test_dict = None
+    path = "./temp_numpy_array"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+
+    def test_numpy_array(self):
+        arrays = {
+            "int": np.arange(12, dtype=np.int32).reshape(3, 4),
+            "float": np.random.random((2, 3, 4)),
+            "big_endian": np.arange(5, dtype=">u2"),
+            "non_contiguous": np.arange(20).reshape(4, 5)[:, ::2],
+            "scalar": np.array(3.5),
+            "empty": np.zeros((0, 3)),
+            "string": np.array(["a", "bc", "def"]),
+        }
+        for k, v in arrays.items():
+            self.test_dict[k] = v
+        for k, v in arrays.items():
+            value = self.test_dict[k]
+            self.assertEqual(value.dtype, v.dtype)
+            self.assertEqual(value.shape, v.shape)
+            self.assertTrue(np.array_equal(value, v))
+            # returned arrays are writable
+            value[...] = v
+
+    def test_object_array_pickled(self):
+        value = np.array([1, "a", None], dtype=object)
+        self.test_dict["object"] = value
+        self.assertEqual(list(self.test_dict["object"]), [1, "a", None])
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestNativeTypes(unittest.TestCase):
+    test_dict = None
+    path = "./temp_native_types"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+
+    def test_values(self):
+        values = [
+            None,
+            datetime(2023, 5, 17, 8, 30, 1, 123456),
+            datetime(1900, 1, 1, tzinfo=timezone(timedelta(hours=-5, minutes=-30))),
+            date(1, 1, 1),
+            date(9999, 12, 31),
+            uuid4(),
+            Decimal("10.00"),
+            Decimal("-0.001"),
+            Decimal("-0"),
+            Decimal("1E+5"),
+            Decimal("Infinity"),
+        ]
+        for i, v in enumerate(values):
+            self.test_dict[i] = v
+        for i, v in enumerate(values):
+            value = self.test_dict[i]
+            self.assertEqual(type(value), type(v))
+            self.assertEqual(str(value), str(v))
+        self.test_dict["nan"] = Decimal("NaN")
+        self.assertTrue(self.test_dict["nan"].is_nan())
+
+    def test_zoned_datetime(self):
+        try:
+            from zoneinfo import ZoneInfo
+
+            zone = ZoneInfo("Europe/Paris")
+        except Exception:
+            self.skipTest("zoneinfo not available")
+        for value in [datetime(2023, 7, 1, 12, tzinfo=zone), datetime(2023, 1, 1, 12, tzinfo=zone)]:
+            self.test_dict["zoned"] = value
+            stored = self.test_dict["zoned"]
+            self.assertEqual(stored, value)
+            self.assertEqual(stored.tzinfo, zone)
+            self.assertEqual(stored.utcoffset(), value.utcoffset())
+
+    def test_keys_order(self):
+        decimals = [
+            Decimal(s)
+            for s in ["-Infinity", "-100", "-9.5", "-0.01", "0", "0.001", "1.5", "1.55", "2", "10", "Infinity"]
+        ]
+        datetimes = [
+            datetime(1969, 12, 31, 23, 59, 59, 999999),
+            datetime(1970, 1, 1),
+            datetime(2024, 2, 29, 12),
+        ]
+        dates = [date(1, 1, 1), date(1999, 12, 31), date(2000, 1, 1)]
+        uuids = sorted([uuid4() for _ in range(10)])
+        cf = self.test_dict.create_column_family("keys")
+        for k in decimals + datetimes + dates + uuids + [None]:
+            cf[k] = True
+        self.assertEqual(
+            list(cf.keys()),
+            [None] + datetimes + dates + uuids + decimals,
+        )
+        # keys are normalized
+        self.assertTrue(cf[Decimal("1.50")])
+        aware = datetime(2020, 1, 1, 12, tzinfo=timezone(timedelta(hours=2)))
+        cf[aware] = "aware"
+        self.assertEqual(cf[aware.astimezone(timezone.utc)], "aware")
+        self.assertEqual(cf[UUID(str(uuids[0]))], True)
+        cf.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestCodec(unittest.TestCase):
+    test_dict = None
+    path = "./temp_codec"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        opt = Options()
+        opt.set_codec(Codec.json())
+        msgpack_opt = Options()
+        msgpack_opt.set_codec(Codec.msgpack())
+        cls.test_dict = Rdict(cls.path, opt, column_families={"msgpack": msgpack_opt})
+
+    def test_json(self):
+        value = {"a": [1, 2.5, None, True], "b": {"c": "d"}}
+        self.test_dict["json"] = value
+        self.test_dict["tuple"] = (1, 2)
+        self.assertEqual(self.test_dict["json"], value)
+        self.assertEqual(self.test_dict["tuple"], [1, 2])
+        self.assertRaises(
+            InvalidArgumentError, lambda: self.test_dict.put("set", {1, 2})
+        )
+
+    def test_msgpack(self):
+        cf = self.test_dict.get_column_family("msgpack")
+        self.assertEqual(cf.get_codec().name, "msgpack")
+        value = {1: b"\x00\x01", "list": [None, -1, 1.5]}
+        cf["msgpack"] = value
+        self.assertEqual(cf["msgpack"], value)
+        # the column family handle carries the codec of the column family
+        wb = WriteBatch()
+        wb.put("batch", {"x": b"y"}, self.test_dict.get_column_family_handle("msgpack"))
+        self.test_dict.write(wb)
+        self.assertEqual(cf["batch"], {"x": b"y"})
+        cf.close()
+
+    def test_custom(self):
+        cf = self.test_dict.create_column_family("custom")
+        cf.set_codec(
+            Codec.custom(
+                "json-text",
+                lambda x: bytes(dumps(x), "utf-8"),
+                lambda x: loads(x.decode("utf-8")),
+            )
+        )
+        cf["custom"] = {"a": 1}
+        self.assertEqual(cf["custom"], {"a": 1})
+        cf.close()
+        self.assertRaises(
+            InvalidArgumentError, lambda: Codec.custom("json", dumps, loads)
+        )
+
+    def test_reopen(self):
+        self.test_dict["reopen"] = {"a": 1}
+        self.test_dict.close()
+        TestCodec.test_dict = Rdict(self.path)
+        self.assertEqual(self.test_dict.get_codec().name, "json")
+        self.assertEqual(self.test_dict["reopen"], {"a": 1})
+        self.assertEqual(
+            self.test_dict.get_column_family("msgpack").get_codec().name, "msgpack"
+        )
+        # custom codecs must be set again after reopening
+        custom = self.test_dict.get_column_family("custom")
+        self.assertEqual(custom.get_codec().name, "json-text")
+        self.assertRaises(InvalidArgumentError, lambda: custom["custom"])
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestValueCompression(unittest.TestCase):
+    test_dict = None
+    path = "./temp_value_compression"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        opt = Options()
+        opt.set_value_compression(ValueCompression.zstd(threshold=100))
+        cls.test_dict = Rdict(cls.path, opt)
+
+    def test_compression(self):
+        doc = {"text": "lorem ipsum " * 1000}
+        self.test_dict["doc"] = doc
+        self.test_dict["small"] = "small"
+        self.test_dict["bytes"] = b"\x00" * 10000
+        self.assertEqual(self.test_dict["doc"], doc)
+        self.assertEqual(self.test_dict["small"], "small")
+        self.assertEqual(self.test_dict["bytes"], b"\x00" * 10000)
+        # previously compressed values remain readable after switching
+        self.test_dict.set_value_compression(ValueCompression.lz4(threshold=100))
+        self.test_dict["lz4"] = "a" * 1000
+        self.test_dict.set_value_compression(None)
+        self.assertEqual(self.test_dict["lz4"], "a" * 1000)
+        self.assertEqual(self.test_dict["doc"], doc)
+
+    def test_reopen(self):
+        self.test_dict["reopen"] = "b" * 1000
+        self.test_dict.close()
+        TestValueCompression.test_dict = Rdict(self.path)
+        self.assertEqual(self.test_dict["reopen"], "b" * 1000)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestExceptions(unittest.TestCase):
+    path = "./temp_exceptions"
+
+    def test_lock_error(self):
+        db = Rdict(self.path)
+        with self.assertRaises(LockError) as cm:
+            Rdict(self.path)
+        self.assertIsInstance(cm.exception, RocksIOError)
+        self.assertIsInstance(cm.exception, RocksDBError)
+        db.close()
+
+    def test_invalid_argument(self):
+        opt = Options()
+        opt.create_if_missing(False)
+        self.assertRaises(InvalidArgumentError, lambda: Rdict(self.path + "_missing", opt))
+        Rdict.destroy(self.path + "_missing")
+
+    @classmethod
+    def tearDownClass(cls):
+        Rdict.destroy(cls.path)
+
+
+class TestMutableMapping(unittest.TestCase):
+    test_dict = None
+    path = "./temp_mutable_mapping"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+
+    def test_mutable_mapping(self):
+        from collections.abc import MutableMapping
+
+        db = self.test_dict
+        self.assertIsInstance(db, MutableMapping)
+        db.clear()
+        self.assertEqual(len(db), 0)
+        db.update({"a": 1, "b": 2})
+        db.update([("c", 3)], d=4)
+        self.assertEqual(len(db), 4)
+        self.assertEqual(list(db), ["a", "b", "c", "d"])
+        self.assertEqual(db, {"a": 1, "b": 2, "c": 3, "d": 4})
+        self.assertNotEqual(db, {"a": 1})
+        self.assertEqual(db.pop("a"), 1)
+        self.assertEqual(db.pop("a", None), None)
+        self.assertRaises(KeyError, lambda: db.pop("a"))
+        self.assertEqual(db.popitem(), ("d", 4))
+        self.assertEqual(db.setdefault("b", 10), 2)
+        self.assertEqual(db.setdefault("e", 5), 5)
+        self.assertEqual(db["e"], 5)
+        self.assertIn("Rdict(", repr(db))
+        db.clear()
+        self.assertEqual(len(db), 0)
+        self.assertRaises(KeyError, db.popitem)
+
+    def test_clear_int_keys(self):
+        cf = self.test_dict.create_column_family("ints")
+        for i in range(-100, 100):
+            cf[i] = i
+        cf.clear()
+        self.assertEqual(len(cf), 0)
+        cf.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestKeyCounts(unittest.TestCase):
+    test_dict = None
+    path = "./temp_key_counts"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+        cls.test_dict.update({"a": 1, "ab": 2, "abc": 3, "b": 4, "c": 5})
+        for i in range(100):
+            cls.test_dict[i] = i
+
+    def test_count(self):
+        self.assertEqual(self.test_dict.count(), 105)
+        self.assertEqual(len(self.test_dict), 105)
+        self.assertEqual(self.test_dict.count(prefix="a"), 3)
+        self.assertEqual(self.test_dict.count(prefix="ab"), 2)
+        self.assertEqual(self.test_dict.count(start="ab", end="c"), 3)
+        self.assertEqual(self.test_dict.count(start="ab", prefix="a"), 2)
+        self.assertEqual(self.test_dict.count(start=10, end=20), 10)
+
+    def test_estimates(self):
+        self.assertGreater(self.test_dict.estimate_num_keys(), 0)
+        self.test_dict.flush()
+        sizes = self.test_dict.approximate_sizes([(0, 100), ("a", "b")])
+        self.assertEqual(len(sizes), 2)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestRangeView(unittest.TestCase):
+    test_dict = None
+    path = "./temp_range_view"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+
+    def setUp(self) -> None:
+        self.test_dict.clear()
+        for i in range(100):
+            self.test_dict[i] = i * i
+
+    def test_iterate(self):
+        view = self.test_dict[10:20]
+        self.assertEqual(list(view), list(range(10, 20)))
+        self.assertEqual(list(reversed(view)), list(range(19, 9, -1)))
+        self.assertEqual(list(view.keys(backwards=True)), list(range(19, 9, -1)))
+        self.assertEqual(list(view.values()), [i * i for i in range(10, 20)])
+        self.assertEqual(list(view.items()), [(i, i * i) for i in range(10, 20)])
+        self.assertEqual(view.count(), 10)
+        self.assertEqual(list(self.test_dict[:3]), [0, 1, 2])
+        self.assertEqual(list(self.test_dict[97:]), [97, 98, 99])
+        self.assertEqual(self.test_dict[:].count(), 100)
+        self.assertRaises(ValueError, lambda: self.test_dict[0:10:2])
+
+    def test_delete(self):
+        self.test_dict[10:20].delete()
+        self.assertEqual(self.test_dict.count(), 90)
+        self.assertNotIn(10, self.test_dict)
+        self.assertIn(20, self.test_dict)
+        self.test_dict[90:].delete()
+        self.assertEqual(list(self.test_dict[85:]), list(range(85, 90)))
+        self.test_dict[:5].delete()
+        self.assertEqual(list(self.test_dict[:7]), [5, 6])
+
+    def test_snapshot(self):
+        snapshot = self.test_dict.snapshot()
+        self.test_dict[10:20].delete()
+        self.assertEqual(list(snapshot[10:13]), [10, 11, 12])
+        self.assertEqual(list(snapshot.keys())[:3], [0, 1, 2])
+        self.assertEqual(len(list(snapshot.keys())), 100)
+        self.assertEqual(snapshot[15:].count(), 85)
+        self.assertRaises(Exception, lambda: snapshot[10:20].delete())
+        del snapshot
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestNamespace(unittest.TestCase):
+    test_dict = None
+    path = "./temp_namespace"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        opt = Options()
+        opt.set_enable_namespaces(True)
+        cls.test_dict = Rdict(cls.path, opt)
+
+    def test_namespace(self):
+        alice = self.test_dict.namespace("alice")
+        bob = self.test_dict.namespace(b"bob")
+        self.test_dict["plain"] = 0
+        for i in range(-5, 5):
+            alice[i] = i
+            bob[i] = -i
+        self.assertEqual(list(alice.keys()), list(range(-5, 5)))
+        self.assertEqual(list(bob.values(backwards=True)), [-i for i in range(4, -6, -1)])
+        self.assertEqual(list(self.test_dict.keys()), ["plain"])
+        self.assertEqual(alice[3], 3)
+        self.assertEqual(bob[[3, 4, 5]], [-3, -4, None])
+        self.assertNotIn("plain", alice)
+        self.assertEqual(len(alice), 10)
+        self.assertEqual(list(alice[0:3]), [0, 1, 2])
+        self.assertEqual(list(alice.items(from_key=3)), [(3, 3), (4, 4)])
+        alice.delete_range(-5, 0)
+        self.assertEqual(list(alice.keys()), list(range(0, 5)))
+        self.assertEqual(alice.popitem(), (4, 4))
+        del alice[0]
+        snapshot = bob.snapshot()
+        bob.clear()
+        self.assertEqual(len(bob), 0)
+        self.assertEqual(len(alice), 3)
+        self.assertEqual(snapshot[2], -2)
+        self.assertEqual(list(snapshot.keys()), list(range(-5, 5)))
+        self.assertEqual(self.test_dict["plain"], 0)
+        self.assertIn("namespace", repr(alice))
+        del snapshot
+
+    def test_not_enabled(self):
+        db = Rdict(self.path + "_disabled")
+        self.assertRaises(NotSupportedError, lambda: db.namespace("alice"))
+        db.close()
+        Rdict.destroy(self.path + "_disabled")
+
+    def test_prefix_extractor(self):
+        opt = Options()
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(3 + len("ns1")))
+        cf = self.test_dict.create_column_family("prefixed", opt)
+        ns1 = cf.namespace("ns1")
+        ns2 = cf.namespace("ns2")
+        for i in range(10):
+            ns1[i] = i
+            ns2[i] = i * 2
+        self.assertEqual(list(ns1.keys()), list(range(10)))
+        self.assertEqual(list(ns2.keys(backwards=True)), list(range(9, -1, -1)))
+        self.assertEqual(ns2.count(), 10)
+        cf.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestOpenAllColumnFamilies(unittest.TestCase):
+    path = "./temp_open_all_column_families"
+
+    def test_open_all(self):
+        opt = Options()
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(3))
+        db = Rdict(self.path)
+        db.create_column_family("cf_a").put("a", 1)
+        db.create_column_family("cf_b", opt).put("b", 2)
+        self.assertEqual(sorted(db.column_families()), ["cf_a", "cf_b", "default"])
+        db.close()
+
+        db = Rdict(
+            self.path,
+            options=Options(),
+            column_families={"cf_a": Options()},
+            open_all_column_families=True,
+        )
+        self.assertEqual(sorted(db.column_families()), ["cf_a", "cf_b", "default"])
+        self.assertEqual(db.get_column_family("cf_b")["b"], 2)
+        db.close()
+
+        db = Rdict(
+            self.path,
+            access_type=AccessType.read_only(),
+            open_all_column_families=True,
+        )
+        self.assertEqual(db.get_column_family("cf_a")["a"], 1)
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestColumnFamilyMetadata(unittest.TestCase):
+    test_dict = None
+    path = "./temp_column_family_metadata"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.test_dict = Rdict(cls.path)
+
+    def test_metadata(self):
+        cf = self.test_dict.create_column_family("meta")
+        for i in range(1000):
+            cf[i] = i
+        cf.flush()
+        meta = cf.column_family_metadata()
+        self.assertEqual(meta["name"], "meta")
+        self.assertEqual(meta["file_count"], 1)
+        self.assertGreater(meta["size"], 0)
+        files = [f for level in meta["levels"] for f in level["files"]]
+        self.assertEqual(len(files), 1)
+        self.assertEqual(files[0]["smallest_key"], 0)
+        self.assertEqual(files[0]["largest_key"], 999)
+        self.assertEqual(files[0]["num_entries"], 1000)
+        cf.close()
+
+    def test_handle(self):
+        self.test_dict.create_column_family("handle")
+        handle = self.test_dict.get_column_family_handle("handle")
+        self.assertEqual(handle.name, "handle")
+        self.assertGreater(handle.id, 0)
+        self.assertEqual(self.test_dict.get_column_family_handle("default").id, 0)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestTableProperties(unittest.TestCase):
+    test_dict = None
+    path = "./temp_table_properties"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        opt = Options()
+        opt.add_compact_on_deletion_collector_factory(100, 50, 0.5)
+        cls.test_dict = Rdict(cls.path, opt)
+        for i in range(100):
+            cls.test_dict[i] = i
+        cls.test_dict.flush()
+        for i in range(100, 200):
+            cls.test_dict[i] = i
+        cls.test_dict.flush()
+
+    def test_all_tables(self):
+        props = self.test_dict.get_properties_of_all_tables()
+        self.assertEqual(len(props), 2)
+        self.assertEqual(sum(p["num_entries"] for p in props.values()), 200)
+        self.assertEqual(
+            sorted((p["start_key"], p["end_key"]) for p in props.values()),
+            [(0, 99), (100, 199)],
+        )
+
+    def test_tables_in_range(self):
+        props = self.test_dict.get_properties_of_tables_in_range([(150, None)])
+        self.assertEqual([p["start_key"] for p in props.values()], [100])
+        props = self.test_dict.get_properties_of_tables_in_range([(0, 100)])
+        self.assertEqual([p["start_key"] for p in props.values()], [0])
+        props = self.test_dict.get_properties_of_tables_in_range([(50, 150)])
+        self.assertEqual(len(props), 2)
+        self.assertEqual(self.test_dict.get_properties_of_tables_in_range([]), {})
+
+    def test_aggregated(self):
+        props = self.test_dict.aggregated_table_properties()
+        self.assertEqual(props["# entries"], 200)
+        self.assertGreater(props["raw value size"], 0)
+        self.assertGreater(props["compression ratio"], 0)
+        self.assertEqual(self.test_dict.aggregated_table_properties(level=0)["# entries"], 200)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.test_dict.close()
+        Rdict.destroy(cls.path)
+
+
+class TestBulkLoader(unittest.TestCase):
+    path = "./temp_bulk_loader"
+
+    def test_ingest_unsorted(self):
+        keys = list(range(-5000, 5000))
+        random_keys = sorted(keys, key=lambda _: random())
+        # small buffer and files to spill runs and split the output
+        loader = BulkLoader(target_file_size=16 * 1024, memory_limit=16 * 1024)
+        for k in random_keys:
+            loader[k] = str(k)
+        # overwrite a few keys, the last value wins
+        loader.update({0: "zero", -5000: "first"})
+        self.assertEqual(len(loader), 10002)
+        db = Rdict(self.path)
+        loader.ingest(db)
+        self.assertEqual(list(db.keys()), keys)
+        self.assertEqual(db[0], "zero")
+        self.assertEqual(db[-5000], "first")
+        self.assertEqual(db[4999], "4999")
+        self.assertGreater(len(db.live_files()), 1)
+        self.assertEqual(len(loader), 0)
+        db.close()
+
+    def test_write(self):
+        loader = BulkLoader()
+        loader.update([(3, 3), (1, 1), (2, 2)])
+        files = loader.write(self.path + "_sst")
+        self.assertEqual(len(files), 1)
+        db = Rdict(self.path)
+        db.ingest_external_file(files)
+        self.assertEqual(list(db.items()), [(1, 1), (2, 2), (3, 3)])
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+        shutil.rmtree(self.path + "_sst", ignore_errors=True)
+
+
+class TestSstFileWriter(unittest.TestCase):
+    path = "./temp_sst_file_writer"
+    sst_path = "./temp_sst_file_writer.sst"
+
+    def test_write_and_ingest(self):
+        db = Rdict(self.path)
+        for i in range(100):
+            db[i] = i
+        writer = SstFileWriter()
+        writer.open(self.sst_path)
+        writer.delete_range(10, 20)
+        writer.put_many([(i, -i) for i in range(50, 60)])
+        del writer[60]
+        writer.put_many({61: 61, 62: 62})
+        writer.finish()
+        info = writer.file_info()
+        self.assertEqual(info["smallest_key"], 50)
+        self.assertEqual(info["largest_key"], 62)
+        self.assertEqual(info["num_entries"], 13)
+        self.assertEqual(info["num_range_del_entries"], 1)
+        self.assertGreater(info["file_size"], 0)
+        db.ingest_external_file([self.sst_path])
+        self.assertEqual(db.count(10, 20), 0)
+        self.assertEqual(db[55], -55)
+        self.assertNotIn(60, db)
+        self.assertEqual(db[62], 62)
+        db.close()
+
+    def test_file_info_before_finish(self):
+        writer = SstFileWriter()
+        with self.assertRaises(Exception):
+            writer.file_info()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+        if os.path.exists(self.sst_path):
+            os.remove(self.sst_path)
+
+
+class TestBlobFiles(unittest.TestCase):
+    path = "./temp_blob_files"
+
+    def test_large_values_in_blob_files(self):
+        opt = Options()
+        opt.set_enable_blob_files(True)
+        opt.set_min_blob_size(1024)
+        opt.set_blob_file_size(1024 * 1024)
+        opt.set_enable_blob_gc(True)
+        opt.set_blob_gc_age_cutoff(0.5)
+        opt.set_blob_gc_force_threshold(0.8)
+        db = Rdict(self.path, opt)
+        for i in range(10):
+            db[i] = b"x" * 4096
+            db[i + 100] = b"small"
+        db.flush()
+        stats = db.blob_stats()
+        self.assertGreaterEqual(stats["num_blob_files"], 1)
+        self.assertGreater(stats["live_blob_file_size"], 10 * 4096)
+        blob_files = [f for f in db.live_files() if f["name"].endswith(".blob")]
+        self.assertEqual(len(blob_files), stats["num_blob_files"])
+        self.assertGreater(sum(f["size"] for f in blob_files), 10 * 4096)
+        self.assertIsNone(blob_files[0]["level"])
+        self.assertEqual(db[5], b"x" * 4096)
+        self.assertEqual(db[105], b"small")
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestWriteBufferManager(unittest.TestCase):
+    paths = ["./temp_write_buffer_manager_0", "./temp_write_buffer_manager_1"]
+
+    def test_shared_budget(self):
+        manager = WriteBufferManager(64 * 1024 * 1024, cache=Cache(128 * 1024 * 1024))
+        self.assertTrue(manager.enabled())
+        self.assertEqual(manager.buffer_size(), 64 * 1024 * 1024)
+        opt = Options()
+        opt.set_write_buffer_manager(manager)
+        dbs = [Rdict(path, opt) for path in self.paths]
+        usage = manager.usage()
+        for db in dbs:
+            for i in range(1000):
+                db[i] = "x" * 100
+        self.assertGreater(manager.usage(), usage)
+        manager.set_buffer_size(32 * 1024 * 1024)
+        manager.set_allow_stall(True)
+        self.assertEqual(manager.buffer_size(), 32 * 1024 * 1024)
+        for db in dbs:
+            db.close()
+
+    def tearDown(self):
+        for path in self.paths:
+            Rdict.destroy(path)
+
+
+class TestRibbonFilter(unittest.TestCase):
+    path = "./temp_ribbon_filter"
+
+    def test_filters(self):
+        opt = Options()
+        block_opts = BlockBasedOptions()
+        block_opts.set_hybrid_ribbon_filter(10.0, 1)
+        block_opts.set_whole_key_filtering(True)
+        block_opts.set_optimize_filters_for_memory(True)
+        opt.set_block_based_table_factory(block_opts)
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        self.assertEqual(db[500], 500)
+        self.assertNotIn(1000, db)
+        self.assertGreater(db.aggregated_table_properties()["filter block size"], 0)
+        db.close()
+        opt = Options()
+        block_opts = BlockBasedOptions()
+        block_opts.set_ribbon_filter(10.0)
+        opt.set_block_based_table_factory(block_opts)
+        db = Rdict(self.path, opt)
+        self.assertEqual(db[500], 500)
+        db.close()
+
+    def test_cache_priority_and_pinning(self):
+        opt = Options()
+        block_opts = BlockBasedOptions()
+        block_opts.set_block_cache(Cache(1024 * 1024))
+        block_opts.set_cache_index_and_filter_blocks(True)
+        block_opts.set_cache_index_and_filter_blocks_with_high_priority(False)
+        block_opts.set_top_level_index_pinning_tier(PinningTier.all())
+        block_opts.set_unpartitioned_pinning_tier(PinningTier.flushed_and_similar())
+        opt.set_block_based_table_factory(block_opts)
+        db = Rdict(self.path, opt)
+        db["a"] = 1
+        db.close()
+        options_files = [f for f in os.listdir(self.path) if f.startswith("OPTIONS-")]
+        with open(os.path.join(self.path, max(options_files))) as f:
+            content = f.read()
+        self.assertIn("cache_index_and_filter_blocks_with_high_priority=false", content)
+        self.assertIn("cache_index_and_filter_blocks=true", content)
+        self.assertIn("top_level_index_pinning=kAll", content)
+        self.assertIn("unpartitioned_pinning=kFlushedAndSimilar", content)
+        self.assertIn("partition_pinning=kFallback", content)
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestCacheStats(unittest.TestCase):
+    path = "./temp_cache_stats"
+
+    def test_capacity(self):
+        cache = Cache(1024 * 1024)
+        self.assertEqual(cache.get_capacity(), 1024 * 1024)
+        cache.set_capacity(2 * 1024 * 1024)
+        self.assertEqual(cache.get_capacity(), 2 * 1024 * 1024)
+        cache = Cache.new_hyper_clock_cache(4 * 1024 * 1024, 4096)
+        self.assertEqual(cache.get_capacity(), 4 * 1024 * 1024)
+
+    def test_load_latest_with_cache(self):
+        opt = Options()
+        block_opts = BlockBasedOptions()
+        block_opts.set_block_size(8 * 1024)
+        opt.set_block_based_table_factory(block_opts)
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        db.close()
+        cache = Cache(1024 * 1024)
+        opt, _ = Options.load_latest(self.path, cache=cache)
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            self.assertEqual(db[i], i)
+        self.assertGreater(cache.get_usage(), 0)
+        db.close()
+        options_files = [f for f in os.listdir(self.path) if f.startswith("OPTIONS-")]
+        with open(os.path.join(self.path, max(options_files))) as f:
+            self.assertIn("block_size=8192", f.read())
+
+    def test_block_cache_entry_stats(self):
+        cache = Cache(8 * 1024 * 1024)
+        opt = Options()
+        block_opts = BlockBasedOptions()
+        block_opts.set_block_cache(cache)
+        block_opts.set_cache_index_and_filter_blocks(True)
+        opt.set_block_based_table_factory(block_opts)
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        for i in range(1000):
+            self.assertEqual(db[i], i)
+        stats = db.block_cache_entry_stats()
+        self.assertEqual(stats["capacity"], 8 * 1024 * 1024)
+        self.assertIn("DataBlock", stats["roles"])
+        self.assertGreater(stats["roles"]["DataBlock"]["count"], 0)
+        self.assertGreater(stats["roles"]["DataBlock"]["size"], 0)
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestOptionsSerialization(unittest.TestCase):
+    path = "./temp_options_serialization"
+
+    def test_to_dict(self):
+        opt = Options()
+        opt.set_write_buffer_size(4 * 1024 * 1024)
+        opt.set_max_background_jobs(3)
+        opt.set_level_compaction_dynamic_level_bytes(False)
+        d = opt.to_dict()
+        self.assertEqual(d["write_buffer_size"], 4 * 1024 * 1024)
+        self.assertEqual(d["max_background_jobs"], 3)
+        self.assertFalse(d["level_compaction_dynamic_level_bytes"])
+        self.assertTrue(d["create_if_missing"])
+        self.assertIn("compression", d)
+
+    def test_round_trip(self):
+        opt = Options()
+        opt.set_write_buffer_size(4 * 1024 * 1024)
+        opt.set_max_write_buffer_number(5)
+        from_dict = Options.from_dict(opt.to_dict())
+        self.assertEqual(from_dict.to_dict(), opt.to_dict())
+        from_string = Options.from_options_string(opt.to_options_string())
+        self.assertEqual(from_string.to_dict(), opt.to_dict())
+
+    def test_from_options_string(self):
+        opt = Options.from_options_string(
+            "write_buffer_size=1048576;max_write_buffer_number=4;create_if_missing=true"
+        )
+        d = opt.to_dict()
+        self.assertEqual(d["write_buffer_size"], 1048576)
+        self.assertEqual(d["max_write_buffer_number"], 4)
+        self.assertRaises(
+            InvalidArgumentError,
+            lambda: Options.from_options_string("no_such_option=1"),
+        )
+        self.assertRaises(
+            InvalidArgumentError,
+            lambda: Options.from_dict({"write_buffer_size": "not a number"}),
+        )
+
+    def test_get_options(self):
+        opt = Options.from_dict({"write_buffer_size": 1048576, "create_if_missing": True})
+        db = Rdict(self.path, opt)
+        db[0] = 0
+        self.assertEqual(db.get_options()["write_buffer_size"], 1048576)
+        db.set_options({"write_buffer_size": str(2 * 1048576)})
+        self.assertEqual(db.get_options()["write_buffer_size"], 2 * 1048576)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestCheckCompatibility(unittest.TestCase):
+    path = "./temp_check_compatibility"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        opt = Options()
+        opt.set_write_buffer_size(1024 * 1024)
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(3))
+        db = Rdict(cls.path, opt)
+        db["abc"] = 1
+        db.close()
+
+    def test_compatible(self):
+        opt = Options()
+        opt.set_write_buffer_size(2 * 1024 * 1024)
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(3))
+        diff = opt.check_compatibility(self.path)["default"]
+        self.assertEqual(diff["incompatible"], {})
+        self.assertEqual(
+            diff["changed"]["write_buffer_size"], (1024 * 1024, 2 * 1024 * 1024)
+        )
+        db = Rdict(self.path, opt, verify_options=True)
+        self.assertEqual(db["abc"], 1)
+        db.close()
+
+    def test_incompatible(self):
+        opt = Options(raw_mode=True)
+        diff = opt.check_compatibility(self.path)["default"]
+        self.assertEqual(diff["incompatible"]["raw_mode"], ("false", "true"))
+        self.assertEqual(
+            diff["incompatible"]["comparator"],
+            ("rocksdict", "leveldb.BytewiseComparator"),
+        )
+        self.assertEqual(
+            diff["incompatible"]["prefix_extractor"], ("fixed_prefix(3)", None)
+        )
+        self.assertRaises(
+            InvalidArgumentError,
+            lambda: Rdict(self.path, opt, verify_options=True),
+        )
+
+    def test_missing_db(self):
+        self.assertEqual(Options().check_compatibility(self.path + "_missing"), {})
+
+    @classmethod
+    def tearDownClass(cls):
+        Rdict.destroy(cls.path)
+
+
+class TestRocksDictConfig(unittest.TestCase):
+    path = "./temp_rocksdict_config"
+
+    def config(self):
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            return loads(f.read())
+
+    def test_metadata(self):
+        db = Rdict(self.path)
+        db["a"] = 1
+        db.close()
+        config = self.config()
+        self.assertEqual(config["version"], 1)
+        self.assertEqual(config["comparator"], "rocksdict")
+        self.assertEqual(config["key_encoding_version"], 1)
+        options_files = [f for f in os.listdir(self.path) if f.startswith("OPTIONS-")]
+        with open(os.path.join(self.path, max(options_files))) as f:
+            self.assertIn("comparator=rocksdict\n", f.read())
+        self.assertIsInstance(config["created_with"], str)
+        self.assertFalse(
+            os.path.exists(os.path.join(self.path, "rocksdict-config.json.tmp"))
+        )
+
+    def test_namespaces_metadata(self):
+        opt = Options()
+        opt.set_enable_namespaces(True)
+        db = Rdict(self.path, opt)
+        db.namespace("ns")["a"] = 1
+        db.close()
+        config = self.config()
+        self.assertEqual(config["comparator"], "rocksdict.v2")
+        self.assertEqual(config["key_encoding_version"], 2)
+        db = Rdict(self.path)
+        self.assertEqual(db.namespace("ns")["a"], 1)
+        db.close()
+
+    def test_migrate_legacy_config(self):
+        db = Rdict(self.path)
+        db["a"] = 1
+        db.close()
+        legacy = {"raw_mode": False, "prefix_extractors": {}}
+        with open(os.path.join(self.path, "rocksdict-config.json"), "w") as f:
+            f.write(dumps(legacy))
+        db = Rdict(self.path)
+        self.assertEqual(db["a"], 1)
+        db.close()
+        config = self.config()
+        self.assertEqual(config["version"], 1)
+        self.assertEqual(config["comparator"], "rocksdict")
+        self.assertIsNone(config["created_with"])
+
+    def test_newer_config(self):
+        db = Rdict(self.path)
+        db.close()
+        config = self.config()
+        config["version"] = 1000
+        with open(os.path.join(self.path, "rocksdict-config.json"), "w") as f:
+            f.write(dumps(config))
+        self.assertRaises(Exception, lambda: Options.load_latest(self.path))
+        self.assertRaises(Exception, lambda: Rdict(self.path, Options()))
+        self.assertEqual(self.config()["version"], 1000)
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestColumnFamilyTtl(unittest.TestCase):
+    path = "./temp_column_family_ttl"
+
+    def test_ttl(self):
+        db = Rdict(
+            self.path,
+            Options(),
+            column_families={"cache": (Options(), 1), "keep": Options()},
+        )
+        for i in range(100):
+            db[i] = i
+            db.get_column_family("cache")[i] = i
+            db.get_column_family("keep")[i] = i
+        db.close()
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            ttls = loads(f.read())["ttls"]
+        self.assertEqual(ttls, {"cache": 1})
+        # reopening without the tuple keeps the ttl
+        db = Rdict(
+            self.path,
+            Options(),
+            column_families={"cache": Options(), "keep": Options()},
+        )
+        time.sleep(2.5)
+        cache = db.get_column_family("cache")
+        keep = db.get_column_family("keep")
+        for cf in (db, cache, keep):
+            cf.compact_range(None, None)
+        self.assertEqual(list(cache.keys()), [])
+        self.assertEqual(list(keep.keys()), list(range(100)))
+        self.assertEqual(list(db.keys()), list(range(100)))
+        db.close()
+        # values carry a timestamp, which read_only DBs cannot strip
+        self.assertRaises(
+            NotSupportedError,
+            lambda: Rdict(self.path, access_type=AccessType.read_only()),
+        )
+        # an `(Options, None)` tuple removes the ttl
+        db = Rdict(
+            self.path,
+            Options(),
+            column_families={"cache": (Options(), None), "keep": Options()},
+        )
+        db.get_column_family("cache")[0] = 0
+        db.close()
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            config = loads(f.read())
+        self.assertEqual(config["ttls"], {})
+        self.assertTrue(config["ttl_timestamps"])
+        db = Rdict(
+            self.path,
+            Options(),
+            column_families={"cache": Options(), "keep": Options()},
+        )
+        time.sleep(2.5)
+        cache = db.get_column_family("cache")
+        cache.compact_range(None, None)
+        self.assertEqual(cache[0], 0)
+        self.assertEqual(db[0], 0)
+        db.close()
+
+    def test_db_without_ttl(self):
+        db = Rdict(self.path, Options(), column_families={"cache": Options()})
+        db.get_column_family("cache")[0] = 0
+        db.close()
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            config = f.read()
+        # values of DBs created without ttl have no timestamp
+        self.assertRaises(
+            InvalidArgumentError,
+            lambda: Rdict(
+                self.path, Options(), column_families={"cache": (Options(), 1)}
+            ),
+        )
+        # the config is not written when the DB is not opened
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            self.assertEqual(f.read(), config)
+        db = Rdict(self.path, Options(), column_families={"cache": Options()})
+        self.assertEqual(db.get_column_family("cache")[0], 0)
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
+class TestCompactionSettings(unittest.TestCase):
+    path = "./temp_compaction_settings"
+
+    def persisted_options(self):
+        options_files = [f for f in os.listdir(self.path) if f.startswith("OPTIONS-")]
+        latest = max(options_files, key=lambda f: int(f.split("-")[1]))
+        options = {}
+        with open(os.path.join(self.path, latest)) as f:
+            for line in f:
+                if "=" in line:
+                    key, value = line.strip().split("=", 1)
+                    options.setdefault(key, value)
+        return options
+
+    def test_compaction_settings(self):
+        opt = Options()
+        opt.set_periodic_compaction_seconds(7 * 24 * 3600)
+        opt.set_ttl(30 * 24 * 3600)
+        opt.set_compaction_pri(DBCompactionPri.round_robin())
+        opt.set_compression_type(DBCompressionType.lz4())
+        opt.set_bottommost_compression_type(DBCompressionType.zstd())
+        opt.set_bottommost_compression_options(-14, 19, 0, 0, True)
+        d = opt.to_dict()
+        self.assertEqual(d["periodic_compaction_seconds"], 7 * 24 * 3600)
+        self.assertEqual(d["bottommost_compression"], "kZSTD")
+        db = Rdict(self.path, opt)
+        db[0] = 0
+        persisted = self.persisted_options()
+        self.assertEqual(persisted["compaction_pri"], "kRoundRobin")
+        self.assertEqual(persisted["ttl"], str(30 * 24 * 3600))
+        self.assertEqual(persisted["bottommost_compression"], "kZSTD")
+        live = db.get_options()
+        self.assertEqual(live["ttl"], 30 * 24 * 3600)
+        self.assertEqual(live["periodic_compaction_seconds"], 7 * 24 * 3600)
+        self.assertEqual(live["compaction_pri"], "kRoundRobin")
+        self.assertEqual(live["bottommost_compression"], "kZSTD")
+        self.assertIn("level=19", live["bottommost_compression_opts"])
+        db.close()
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+
 class TestColumnFamiliesDefaultOpts(unittest.TestCase):
     test_dict = None
     path = "./column_families"